@@ -0,0 +1,6 @@
+fn main() {
+    // Targets that rustc doesn't know about (yet, or anymore)
+    println!(
+        "cargo:rustc-check-cfg=cfg(target_os, values(\"bitrig\", \"daku\"))"
+    );
+}
@@ -3,7 +3,7 @@ use std::{
     io::{Error, ErrorKind},
 };
 
-use crate::Result;
+use crate::{DistroInfo, Language, Result};
 
 pub(crate) fn string_from_os(string: OsString) -> Result<String> {
     #[cfg(any(
@@ -30,3 +30,693 @@ pub(crate) fn string_from_os(string: OsString) -> Result<String> {
         })
     }
 }
+
+/// Convert a POSIX locale name or BCP 47 language tag into a [`Language`],
+/// dropping the encoding and modifier (`en_US.UTF-8@euro` becomes `en-US`).
+///
+/// The tag is normalized to the conventional casing of each subtag, so that
+/// equal languages always have equal tags.
+pub(crate) fn language_from_locale(locale: &str) -> Option<Language> {
+    let tag = locale.split(|c| c == '.' || c == '@').next()?.trim();
+
+    if tag.is_empty() {
+        return None;
+    }
+
+    let subtags: Vec<String> = tag
+        .split(|c| c == '_' || c == '-')
+        .enumerate()
+        .map(|(i, subtag)| match (i, subtag.len()) {
+            // Region (example: `US`)
+            (n, 2) if n >= 1 => subtag.to_ascii_uppercase(),
+            // Script (example: `Hans`)
+            (n, 4) if n >= 1 => {
+                let mut subtag = subtag.to_ascii_lowercase();
+
+                if let Some(first) = subtag.get_mut(..1) {
+                    first.make_ascii_uppercase();
+                }
+
+                subtag
+            }
+            _ => subtag.to_ascii_lowercase(),
+        })
+        .collect();
+
+    Some(Language::__(Box::new(subtags.join("-"))))
+}
+
+/// Convert the BCP 47 tags of `navigator.languages` into [`Language`]s, or
+/// else the tag of `navigator.language` if there are none.
+///
+/// Values that aren't strings (`None`) or valid tags are skipped.
+#[allow(dead_code)] // Not used on all targets
+pub(crate) fn languages_from_navigator(
+    languages: impl IntoIterator<Item = Option<String>>,
+    language: impl FnOnce() -> Option<String>,
+) -> Vec<Language> {
+    let mut langs: Vec<Language> = languages
+        .into_iter()
+        .filter_map(|lang| language_from_locale(&lang?))
+        .collect();
+
+    // Fall back to the browser's UI language
+    if langs.is_empty() {
+        langs.extend(language().and_then(|lang| language_from_locale(&lang)));
+    }
+
+    langs
+}
+
+/// Look up the value of a `KEY=value` line, as found in `/etc/os-release` and
+/// `/etc/lsb-release`.
+#[allow(dead_code)] // Not used on all targets
+pub(crate) fn release_value<'a>(data: &'a str, key: &str) -> Option<&'a str> {
+    data.lines().find_map(|line| {
+        let mut pair = line.splitn(2, '=');
+
+        if pair.next()?.trim() != key {
+            return None;
+        }
+
+        let value = pair.next()?.trim().trim_matches('"').trim_matches('\'');
+
+        if value.is_empty() {
+            None
+        } else {
+            Some(value)
+        }
+    })
+}
+
+/// Parse the identification fields from the contents of `/etc/os-release`.
+#[allow(dead_code)] // Not used on all targets
+pub(crate) fn distro_info_os_release(data: &str) -> DistroInfo {
+    let value = |key| release_value(data, key).map(ToString::to_string);
+
+    DistroInfo {
+        pretty_name: value("PRETTY_NAME"),
+        name: value("NAME"),
+        id: value("ID"),
+        version_id: value("VERSION_ID"),
+        version_codename: value("VERSION_CODENAME"),
+        build_id: value("BUILD_ID"),
+        id_like: release_value(data, "ID_LIKE")
+            .map(|ids| {
+                ids.split_whitespace().map(ToString::to_string).collect()
+            })
+            .unwrap_or_default(),
+        home_url: value("HOME_URL"),
+    }
+}
+
+/// Get the distribution's name and version from the contents of
+/// `/etc/os-release`.
+#[allow(dead_code)] // Not used on all targets
+pub(crate) fn distro_os_release(data: &str) -> Option<String> {
+    if let Some(pretty_name) = release_value(data, "PRETTY_NAME") {
+        return Some(pretty_name.to_string());
+    }
+
+    let name = release_value(data, "NAME")?;
+
+    Some(
+        if let Some(version) = release_value(data, "VERSION")
+            .or_else(|| release_value(data, "VERSION_ID"))
+        {
+            join_name_version(name, version)
+        } else {
+            name.to_string()
+        },
+    )
+}
+
+/// Combine a distribution's name and version, without repeating the name when
+/// the version already includes it (or the other way around).  Only whole
+/// words count, so "Foo 11" doesn't include the version "1".
+#[allow(dead_code)] // Not used on all targets
+pub(crate) fn join_name_version(name: &str, version: &str) -> String {
+    if version.starts_with(name)
+        && version[name.len()..]
+            .chars()
+            .next()
+            .map_or(true, char::is_whitespace)
+    {
+        version.to_string()
+    } else if name.ends_with(version)
+        && name[..name.len() - version.len()]
+            .chars()
+            .next_back()
+            .map_or(true, char::is_whitespace)
+    {
+        name.to_string()
+    } else {
+        format!("{} {}", name, version)
+    }
+}
+
+/// Get the browser's name from the user agent.
+#[allow(dead_code)] // Not used on all targets
+pub(crate) fn browser_from_user_agent(
+    user_agent: &str,
+) -> Option<&'static str> {
+    // Browsers claim to be other browsers for compatibility, so check for the
+    // most specific tokens first.
+    const BROWSERS: &[(&str, &str)] = &[
+        ("Edg/", "Edge"),
+        ("EdgA/", "Edge"),
+        ("EdgiOS/", "Edge"),
+        ("OPR/", "Opera"),
+        ("SamsungBrowser/", "Samsung Internet"),
+        ("Firefox/", "Firefox"),
+        ("FxiOS/", "Firefox"),
+        ("CriOS/", "Chrome"),
+        ("Chromium/", "Chromium"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+    ];
+
+    let browser = BROWSERS
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, browser)| *browser)?;
+
+    // GNOME Web (Epiphany) only identifies as Safari
+    if browser == "Safari" && user_agent.contains("Linux") {
+        return Some("GNOME Web");
+    }
+
+    Some(browser)
+}
+
+/// Get the host operating system (and possibly version) from the user agent.
+#[allow(dead_code)] // Not used on all targets
+pub(crate) fn os_from_user_agent(user_agent: &str) -> Option<String> {
+    let begin = user_agent.find('(')?;
+    let end = begin + user_agent[begin..].find(')')?;
+    let fields: Vec<&str> = user_agent[begin + 1..end]
+        .split(';')
+        .map(str::trim)
+        .collect();
+    let version = |prefix: &str| {
+        fields.iter().find_map(|field| {
+            let start = field.find(prefix)? + prefix.len();
+
+            Some(
+                field[start..]
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .replace('_', "."),
+            )
+        })
+    };
+
+    if let Some(nt) = version("Windows NT ") {
+        let windows = match nt.as_str() {
+            "10.0" => "Windows 10",
+            "6.3" => "Windows 8.1",
+            "6.2" => "Windows 8",
+            "6.1" => "Windows 7",
+            _ => "Windows",
+        };
+
+        Some(windows.to_string())
+    } else if let Some(ios) = version("iPhone OS ") {
+        Some(format!("iOS {}", ios))
+    } else if fields.iter().any(|f| f.starts_with("iPad")) {
+        Some(
+            version("CPU OS ")
+                .map_or("iPadOS".to_string(), |v| format!("iPadOS {}", v)),
+        )
+    } else if let Some(mac) = version("Mac OS X ") {
+        Some(format!("Mac OS X {}", mac))
+    } else if let Some(android) = version("Android ") {
+        Some(format!("Android {}", android))
+    } else if fields.iter().any(|f| f.starts_with("CrOS")) {
+        Some("Chrome OS".to_string())
+    } else if fields.iter().any(|f| f.contains("Linux")) {
+        // Some browsers name the distribution (example: "X11; Ubuntu; Linux")
+        Some(
+            fields
+                .iter()
+                .find(|f| {
+                    !f.contains("Linux")
+                        && !f.starts_with("X11")
+                        && !f.starts_with("Wayland")
+                        && !f.starts_with("rv:")
+                        && **f != "U"
+                })
+                .map_or("Linux".to_string(), |distro| distro.to_string()),
+        )
+    } else {
+        ["FreeBSD", "OpenBSD", "NetBSD", "Fuchsia"]
+            .iter()
+            .find(|os| fields.iter().any(|f| f.contains(*os)))
+            .map(|os| os.to_string())
+    }
+}
+
+/// Describe the browser and host operating system from the user agent
+/// (example: "Firefox on Windows 10").
+///
+/// The `platform` from the User-Agent Client Hints API (example: "macOS") is
+/// preferred over the user agent's, which browsers may freeze at an old
+/// version.
+#[allow(dead_code)] // Not used on all targets
+pub(crate) fn distro_from_user_agent(
+    user_agent: &str,
+    platform: Option<String>,
+) -> String {
+    let os = platform
+        .filter(|platform| !platform.is_empty() && platform != "Unknown")
+        .or_else(|| os_from_user_agent(user_agent));
+
+    match (browser_from_user_agent(user_agent), os) {
+        (Some(browser), Some(os)) => format!("{} on {}", browser, os),
+        (Some(browser), None) => browser.to_string(),
+        (None, Some(os)) => os,
+        (None, None) => "Web".to_string(),
+    }
+}
+
+/// Decode a punycode-encoded label (without the `xn--` prefix), as specified
+/// by RFC 3492.
+///
+/// Returns `None` for invalid punycode, and for labels without any non-ASCII
+/// characters (such as "abc-"), which IDNA never encodes.
+#[cfg(feature = "idn")]
+pub(crate) fn punycode_decode(input: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+
+    fn adapt(delta: u32, num_points: u32, first: bool) -> u32 {
+        let mut delta = if first { delta / 700 } else { delta / 2 };
+        let mut k = 0;
+
+        delta += delta / num_points;
+
+        while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+            delta /= BASE - T_MIN;
+            k += BASE;
+        }
+
+        k + (BASE - T_MIN + 1) * delta / (delta + 38)
+    }
+
+    let (basic, extended) = match input.rfind('-') {
+        Some(delimiter) => (&input[..delimiter], &input[delimiter + 1..]),
+        None => ("", input),
+    };
+
+    if !basic.is_ascii() || extended.is_empty() {
+        return None;
+    }
+
+    let mut output: Vec<char> = basic.chars().collect();
+    let mut digits = extended.bytes().peekable();
+    let (mut n, mut i, mut bias) = (128u32, 0u32, 72u32);
+
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut weight = 1u32;
+        let mut k = BASE;
+
+        loop {
+            let digit = u32::from(match digits.next()? {
+                digit @ b'a'..=b'z' => digit - b'a',
+                digit @ b'A'..=b'Z' => digit - b'A',
+                digit @ b'0'..=b'9' => digit - b'0' + 26,
+                _ => return None,
+            });
+
+            i = i.checked_add(digit.checked_mul(weight)?)?;
+
+            let threshold = k.saturating_sub(bias).max(T_MIN).min(T_MAX);
+
+            if digit < threshold {
+                break;
+            }
+
+            weight = weight.checked_mul(BASE - threshold)?;
+            k += BASE;
+        }
+
+        let len = output.len() as u32 + 1;
+
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(i as usize, std::char::from_u32(n)?);
+        i += 1;
+    }
+
+    Some(output.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn navigator_languages() {
+        let tags = |languages: &[Option<&str>], language: Option<&str>| {
+            languages_from_navigator(
+                languages.iter().map(|lang| lang.map(String::from)),
+                || language.map(String::from),
+            )
+            .into_iter()
+            .map(|language| language.to_string())
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            tags(&[Some("de-DE"), Some("de"), Some("en-US")], Some("fr")),
+            ["de-DE", "de", "en-US"],
+        );
+        assert_eq!(tags(&[Some("zh-hant-tw")], None), ["zh-Hant-TW"]);
+        assert_eq!(tags(&[None, Some(""), Some("ja")], None), ["ja"]);
+        assert_eq!(tags(&[], Some("fr-CA")), ["fr-CA"]);
+        assert_eq!(tags(&[None, Some("")], Some("fr-CA")), ["fr-CA"]);
+        assert!(tags(&[], Some("")).is_empty());
+        assert!(tags(&[], None).is_empty());
+    }
+
+    const CHROME: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) \
+        AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 \
+        Safari/537.36";
+    const EDGE: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) \
+        AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 \
+        Safari/537.36 Edg/120.0.0.0";
+    const FIREFOX: &str = "Mozilla/5.0 (X11; Ubuntu; Linux x86_64; \
+        rv:121.0) Gecko/20100101 Firefox/121.0";
+    const SAFARI: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) \
+        AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 \
+        Safari/605.1.15";
+    const SAMSUNG: &str = "Mozilla/5.0 (Linux; Android 13; SAMSUNG \
+        SM-S918B) AppleWebKit/537.36 (KHTML, like Gecko) \
+        SamsungBrowser/23.0 Chrome/115.0.0.0 Mobile Safari/537.36";
+
+    #[test]
+    fn user_agent_browser() {
+        assert_eq!(browser_from_user_agent(CHROME), Some("Chrome"));
+        assert_eq!(browser_from_user_agent(EDGE), Some("Edge"));
+        assert_eq!(browser_from_user_agent(FIREFOX), Some("Firefox"));
+        assert_eq!(browser_from_user_agent(SAFARI), Some("Safari"));
+        assert_eq!(browser_from_user_agent(SAMSUNG), Some("Samsung Internet"));
+        assert_eq!(browser_from_user_agent("curl/8.4.0"), None);
+    }
+
+    #[test]
+    fn user_agent_distro() {
+        let distro = |user_agent| distro_from_user_agent(user_agent, None);
+
+        assert_eq!(distro(CHROME), "Chrome on Windows 10");
+        assert_eq!(distro(EDGE), "Edge on Windows 10");
+        assert_eq!(distro(FIREFOX), "Firefox on Ubuntu");
+        assert_eq!(distro(SAFARI), "Safari on Mac OS X 10.15.7");
+        assert_eq!(distro(SAMSUNG), "Samsung Internet on Android 13");
+        assert_eq!(distro("curl/8.4.0"), "Web");
+        assert_eq!(distro(""), "Web");
+    }
+
+    #[test]
+    fn user_agent_platform() {
+        let distro = |platform: &str| {
+            distro_from_user_agent(SAFARI, Some(platform.to_string()))
+        };
+
+        assert_eq!(distro("macOS"), "Safari on macOS");
+        assert_eq!(distro(""), "Safari on Mac OS X 10.15.7");
+        assert_eq!(distro("Unknown"), "Safari on Mac OS X 10.15.7");
+    }
+
+    #[test]
+    fn user_agent_os() {
+        let os = os_from_user_agent;
+
+        assert_eq!(os(CHROME).as_deref(), Some("Windows 10"));
+        assert_eq!(os(SAFARI).as_deref(), Some("Mac OS X 10.15.7"));
+        assert_eq!(
+            os("Mozilla/5.0 (iPhone; CPU iPhone OS 17_2 like Mac OS X) \
+                AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 \
+                Mobile/15E148 Safari/604.1")
+            .as_deref(),
+            Some("iOS 17.2"),
+        );
+        assert_eq!(os(SAMSUNG).as_deref(), Some("Android 13"));
+        assert_eq!(
+            os("Mozilla/5.0 (X11; CrOS x86_64 14541.0.0) \
+                AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 \
+                Safari/537.36")
+            .as_deref(),
+            Some("Chrome OS"),
+        );
+        assert_eq!(
+            os("Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 \
+                Firefox/121.0")
+            .as_deref(),
+            Some("Linux"),
+        );
+        assert_eq!(os(FIREFOX).as_deref(), Some("Ubuntu"));
+        assert_eq!(os("curl/8.4.0"), None);
+    }
+
+    const NIXOS: &str = r#"ANSI_COLOR="1;34"
+BUG_REPORT_URL="https://github.com/NixOS/nixpkgs/issues"
+BUILD_ID="23.11.20240115.b8dd8be"
+DOCUMENTATION_URL="https://nixos.org/learn.html"
+HOME_URL="https://nixos.org/"
+ID=nixos
+IMAGE_ID=""
+IMAGE_VERSION=""
+LOGO="nix-snowflake"
+NAME=NixOS
+PRETTY_NAME="NixOS 23.11 (Tapir)"
+SUPPORT_END="2024-06-30"
+SUPPORT_URL="https://nixos.org/community.html"
+VERSION="23.11 (Tapir)"
+VERSION_CODENAME=tapir
+VERSION_ID="23.11"
+"#;
+
+    #[test]
+    fn os_release_nixos() {
+        let info = distro_info_os_release(NIXOS);
+
+        assert_eq!(info.pretty_name.as_deref(), Some("NixOS 23.11 (Tapir)"));
+        assert_eq!(info.id.as_deref(), Some("nixos"));
+        assert_eq!(info.version_id.as_deref(), Some("23.11"));
+        assert_eq!(info.version_codename.as_deref(), Some("tapir"));
+        assert_eq!(info.build_id.as_deref(), Some("23.11.20240115.b8dd8be"));
+        assert_eq!(
+            distro_os_release(NIXOS).as_deref(),
+            Some("NixOS 23.11 (Tapir)")
+        );
+
+        // Without `PRETTY_NAME`, the codename is kept from `VERSION`
+        let unpretty = NIXOS.replace("PRETTY_NAME", "IGNORED");
+
+        assert_eq!(
+            distro_os_release(&unpretty).as_deref(),
+            Some("NixOS 23.11 (Tapir)"),
+        );
+    }
+
+    #[test]
+    fn os_release_info() {
+        let ubuntu = r#"PRETTY_NAME="Ubuntu 22.04.3 LTS"
+NAME="Ubuntu"
+VERSION_ID="22.04"
+VERSION="22.04.3 LTS (Jammy Jellyfish)"
+VERSION_CODENAME=jammy
+ID=ubuntu
+ID_LIKE=debian
+HOME_URL="https://www.ubuntu.com/"
+SUPPORT_URL="https://help.ubuntu.com/"
+BUG_REPORT_URL="https://bugs.launchpad.net/ubuntu/"
+UBUNTU_CODENAME=jammy
+"#;
+        let fedora = r#"NAME="Fedora Linux"
+VERSION="39 (Workstation Edition)"
+ID=fedora
+VERSION_ID=39
+VERSION_CODENAME=""
+PLATFORM_ID="platform:f39"
+PRETTY_NAME="Fedora Linux 39 (Workstation Edition)"
+ANSI_COLOR="0;38;2;60;110;180"
+CPE_NAME="cpe:/o:fedoraproject:fedora:39"
+HOME_URL="https://fedoraproject.org/"
+VARIANT="Workstation Edition"
+VARIANT_ID=workstation
+"#;
+        let arch = r#"NAME="Arch Linux"
+PRETTY_NAME="Arch Linux"
+ID=arch
+BUILD_ID=rolling
+ANSI_COLOR="38;2;23;147;209"
+HOME_URL="https://archlinux.org/"
+LOGO=archlinux-logo
+"#;
+        let tumbleweed = r#"NAME="openSUSE Tumbleweed"
+# VERSION="20240115"
+ID="opensuse-tumbleweed"
+ID_LIKE="opensuse suse"
+VERSION_ID="20240115"
+PRETTY_NAME="openSUSE Tumbleweed"
+HOME_URL="https://www.opensuse.org"
+"#;
+        let some = |value: &str| Some(value.to_string());
+
+        for (os_release, expected) in &[
+            (
+                ubuntu,
+                DistroInfo {
+                    pretty_name: some("Ubuntu 22.04.3 LTS"),
+                    name: some("Ubuntu"),
+                    id: some("ubuntu"),
+                    version_id: some("22.04"),
+                    version_codename: some("jammy"),
+                    build_id: None,
+                    id_like: vec!["debian".to_string()],
+                    home_url: some("https://www.ubuntu.com/"),
+                },
+            ),
+            (
+                fedora,
+                DistroInfo {
+                    pretty_name: some("Fedora Linux 39 (Workstation Edition)"),
+                    name: some("Fedora Linux"),
+                    id: some("fedora"),
+                    version_id: some("39"),
+                    version_codename: None,
+                    build_id: None,
+                    id_like: Vec::new(),
+                    home_url: some("https://fedoraproject.org/"),
+                },
+            ),
+            (
+                arch,
+                DistroInfo {
+                    pretty_name: some("Arch Linux"),
+                    name: some("Arch Linux"),
+                    id: some("arch"),
+                    version_id: None,
+                    version_codename: None,
+                    build_id: some("rolling"),
+                    id_like: Vec::new(),
+                    home_url: some("https://archlinux.org/"),
+                },
+            ),
+            (
+                tumbleweed,
+                DistroInfo {
+                    pretty_name: some("openSUSE Tumbleweed"),
+                    name: some("openSUSE Tumbleweed"),
+                    id: some("opensuse-tumbleweed"),
+                    version_id: some("20240115"),
+                    version_codename: None,
+                    build_id: None,
+                    id_like: vec!["opensuse".to_string(), "suse".to_string()],
+                    home_url: some("https://www.opensuse.org"),
+                },
+            ),
+            (
+                NIXOS,
+                DistroInfo {
+                    pretty_name: some("NixOS 23.11 (Tapir)"),
+                    name: some("NixOS"),
+                    id: some("nixos"),
+                    version_id: some("23.11"),
+                    version_codename: some("tapir"),
+                    build_id: some("23.11.20240115.b8dd8be"),
+                    id_like: Vec::new(),
+                    home_url: some("https://nixos.org/"),
+                },
+            ),
+            ("", DistroInfo::default()),
+        ] {
+            assert_eq!(
+                &distro_info_os_release(os_release),
+                expected,
+                "{}",
+                os_release,
+            );
+        }
+    }
+
+    #[test]
+    fn os_release_name_version() {
+        for (os_release, expected) in &[
+            (
+                "NAME=\"Ubuntu\"\nVERSION=\"22.04.3 LTS (Jammy Jellyfish)\"\n\
+                 PRETTY_NAME=\"Ubuntu 22.04.3 LTS\"\n",
+                Some("Ubuntu 22.04.3 LTS"),
+            ),
+            (
+                "NAME=\"Ubuntu\"\nVERSION=\"22.04.3 LTS (Jammy Jellyfish)\"\n",
+                Some("Ubuntu 22.04.3 LTS (Jammy Jellyfish)"),
+            ),
+            (
+                "NAME=Ubuntu\nVERSION=\"Ubuntu 22.04\"\n",
+                Some("Ubuntu 22.04"),
+            ),
+            (
+                "NAME=\"Fedora Linux\"\nVERSION=\"39 (Workstation Edition)\"\n",
+                Some("Fedora Linux 39 (Workstation Edition)"),
+            ),
+            (
+                "NAME=\"Arch Linux\"\nVERSION_ID=rolling\n",
+                Some("Arch Linux rolling"),
+            ),
+            ("NAME=\"Void 2024\"\nVERSION_ID=2024\n", Some("Void 2024")),
+            ("NAME=\"Foo 1\"\nVERSION=\"1.2\"\n", Some("Foo 1 1.2")),
+            ("NAME=\"Foo 11\"\nVERSION=1\n", Some("Foo 11 1")),
+            ("NAME=Foo\nVERSION=\"Foobar 2\"\n", Some("Foo Foobar 2")),
+            ("NAME=Foo\nVERSION=Foo\n", Some("Foo")),
+            ("NAME=\"Arch Linux\"\n", Some("Arch Linux")),
+            ("PRETTY_NAME=\"\"\nNAME=NixOS\n", Some("NixOS")),
+            ("ID=debian\nVERSION_ID=12\n", None),
+            ("", None),
+        ] {
+            assert_eq!(
+                distro_os_release(os_release).as_deref(),
+                *expected,
+                "{:?}",
+                os_release,
+            );
+        }
+    }
+
+    #[cfg(feature = "idn")]
+    #[test]
+    fn punycode() {
+        for (label, expected) in &[
+            ("mnchen-3ya", Some("münchen")),
+            ("bcher-kva", Some("bücher")),
+            ("fiqs8s", Some("中国")),
+            ("wgv71a119e", Some("日本語")),
+            ("tda", Some("ü")),
+            // Bad digit
+            ("mnchen-3y!", None),
+            ("mnchen-3yä", None),
+            // Non-ASCII basic code points
+            ("münchen-3ya", None),
+            // Overflow
+            ("zzzzzzzzzzzzzzzzzzzz", None),
+            // Ends partway through a code point
+            ("mnchen-3y", None),
+            // Trailing delimiter (nothing encoded)
+            ("abc-", None),
+            ("", None),
+        ] {
+            assert_eq!(
+                punycode_decode(label).as_deref(),
+                *expected,
+                "{}",
+                label
+            );
+        }
+    }
+}
@@ -3,13 +3,33 @@
 //! Some of the functions in the root module will return "Unknown" or
 //! "localhost" on error.  This might not be desirable in some situations.  The
 //! functions in this module all return a [`Result`].
+//!
+//! With the `mock-env` feature, each function first checks for a
+//! `WHOAMI_<FIELD>` environment variable (`WHOAMI_USERNAME`, `WHOAMI_REALNAME`,
+//! `WHOAMI_DEVICENAME`, `WHOAMI_HOSTNAME` or `WHOAMI_DISTRO`), and returns its
+//! value unchanged if it's set.  This is intended for reproducible tests, and
+//! should never be enabled in production builds.
+
+use std::{
+    env,
+    ffi::OsString,
+    io::{Error, ErrorKind},
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Mutex, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
 
-use std::ffi::OsString;
+use lazy_static::lazy_static;
 
+#[cfg(feature = "mock-env")]
+use crate::mock;
 use crate::{
     conversions,
     os::{Os, Target},
-    Result,
+    DistroInfo, GroupId, Language, NameKind, Result, UnameInfo, UserId,
 };
 
 /// Get the user's username.
@@ -27,10 +47,109 @@ pub fn username() -> Result<String> {
 /// spaces are not allowed in the username.
 #[inline(always)]
 pub fn username_os() -> Result<OsString> {
+    #[cfg(feature = "mock-env")]
+    {
+        if let Some(username) = mock::var_os("USERNAME") {
+            return Ok(username);
+        }
+    }
+
     Target::username(Os)
 }
 
+/// Get the user's username in lowercase, for case-insensitive comparison
+/// (such as with Active Directory or LDAP records).
+///
+/// Use [`username()`] to get the username in its original case.
+#[inline(always)]
+pub fn username_normalized() -> Result<String> {
+    username().map(|username| username.to_lowercase())
+}
+
+/// Get the user's username and real (full) name, with a single lookup.
+///
+/// The real name falls back to the username if it's empty or not valid UTF-8,
+/// so neither is empty.  Useful for displaying a user as "jdoe (Jane Doe)".
+/// On unix-systems the real name is only taken from gecos, without the other
+/// sources [`realname()`] falls back to.
+pub fn user() -> Result<(String, String)> {
+    let (username, realname) = Target::user(Os)?;
+    let username = conversions::string_from_os(username)?;
+    let realname = conversions::string_from_os(realname)
+        .ok()
+        .filter(|realname| !realname.is_empty())
+        .unwrap_or_else(|| username.clone());
+
+    Ok((username, realname))
+}
+
+/// Get the login name of the user logged in on the controlling terminal.
+///
+/// On unix-systems this differs from [`username()`], which is the name of the
+/// effective user, after `su` or running a setuid program.  Fails with
+/// [`ErrorKind::NotFound`](std::io::ErrorKind::NotFound) when there's no
+/// controlling terminal (such as in daemons).  On other systems this is the
+/// same as [`username_os()`].
+#[inline(always)]
+pub fn login_name() -> Result<OsString> {
+    Target::login(Os)
+}
+
+/// Get the effective user ID.
+///
+/// Only supported on unix-systems; fails with
+/// [`ErrorKind::Other`](std::io::ErrorKind::Other) elsewhere.
+#[inline(always)]
+pub fn uid() -> Result<UserId> {
+    Target::uid(Os)
+}
+
+/// Get the effective group ID.
+///
+/// Only supported on unix-systems; fails with
+/// [`ErrorKind::Other`](std::io::ErrorKind::Other) elsewhere.
+#[inline(always)]
+pub fn gid() -> Result<GroupId> {
+    Target::gid(Os)
+}
+
+/// Get the username of the user who ran the program.
+///
+/// Unlike [`username()`], this sees through `sudo` and `doas` (via the
+/// `SUDO_USER` and `DOAS_USER` environment variables), and then tries the user
+/// logged in on the controlling terminal, before falling back to
+/// [`username_os()`].
+pub fn login_user() -> Result<OsString> {
+    login_user_from(
+        |name| env::var_os(name),
+        || login_name().or_else(|_| username_os()),
+    )
+}
+
+/// Get the user from `SUDO_USER` or `DOAS_USER` in `var`, or else `fallback`.
+fn login_user_from(
+    var: impl Fn(&str) -> Option<OsString>,
+    fallback: impl FnOnce() -> Result<OsString>,
+) -> Result<OsString> {
+    for name in &["SUDO_USER", "DOAS_USER"] {
+        if let Some(user) = var(name).filter(|user| !user.is_empty()) {
+            return Ok(user);
+        }
+    }
+
+    fallback()
+}
+
 /// Get the user's real (full) name.
+///
+/// On unix-systems this is the first field of gecos.  If that's empty, it's
+/// looked up with Identity Services on macOS, and then taken from the
+/// `FULLNAME` or `NAME` environment variable (in that order), and may still be
+/// empty.
+///
+/// Fails with [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) if
+/// the name isn't valid UTF-8 (such as a gecos field in a legacy encoding);
+/// use [`realname_os()`] to get the raw name instead.
 #[inline(always)]
 pub fn realname() -> Result<String> {
     realname_os().and_then(conversions::string_from_os)
@@ -39,17 +158,210 @@ pub fn realname() -> Result<String> {
 /// Get the user's real (full) name.
 #[inline(always)]
 pub fn realname_os() -> Result<OsString> {
+    #[cfg(feature = "mock-env")]
+    {
+        if let Some(realname) = mock::var_os("REALNAME") {
+            return Ok(realname);
+        }
+    }
+
     Target::realname(Os)
 }
 
+/// Get the user's most preferred language.
+///
+/// This is the first of [`langs()`](crate::langs()), in its most specific form
+/// (example: `de-DE` rather than `de`).  Fails with
+/// [`ErrorKind::NotFound`] if there are no preferred languages.
+pub fn primary_language() -> Result<Language> {
+    #[cfg(feature = "mock-env")]
+    {
+        if let Some(list) = mock::var("LANGS") {
+            return list
+                .split(':')
+                .find_map(conversions::language_from_locale)
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::NotFound, "No languages")
+                });
+        }
+    }
+
+    Target::primary_lang(Os)
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "No languages"))
+}
+
 /// Get the name of the operating system distribution and (possibly) version.
 ///
 /// Example: "Windows 10" or "Fedora 26 (Workstation Edition)"
+///
+/// Rolling release distributions (such as Arch Linux) may not have a version.
+/// On Gentoo, the Portage profile or baselayout release is added instead
+/// (example: "Gentoo Linux, profile default/linux/amd64/23.0/desktop").
 #[inline(always)]
 pub fn distro() -> Result<String> {
+    #[cfg(feature = "mock-env")]
+    {
+        if let Some(distro) = mock::var("DISTRO") {
+            return Ok(distro);
+        }
+    }
+
     Target::distro(Os)
 }
 
+/// Get the codename of the operating system distribution's release, from
+/// `VERSION_CODENAME` in `/etc/os-release`.
+///
+/// Example: "bookworm" for "Debian GNU/Linux 12 (bookworm)"
+///
+/// Fails with [`ErrorKind::NotFound`] if the release has no codename (example:
+/// "Fedora Linux 39 (Workstation Edition)", where the parenthetical is the
+/// edition).
+pub fn distro_codename() -> Result<String> {
+    distro_info()?
+        .version_codename
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "No codename"))
+}
+
+/// Get the name of the operating system distribution and (possibly) version,
+/// without the codename.
+///
+/// Example: "Debian GNU/Linux 12" for "Debian GNU/Linux 12 (bookworm)"
+///
+/// Only a trailing parenthetical matching [`distro_codename()`] is removed, so
+/// other parentheticals (example: "Fedora Linux 39 (Workstation Edition)") are
+/// kept.
+pub fn distro_clean() -> Result<String> {
+    let distro = distro()?;
+    let codename = distro_codename().ok();
+
+    Ok(strip_codename(&distro, codename.as_deref()).to_string())
+}
+
+/// Remove the trailing parenthetical from a distribution's name, if it's the
+/// codename.
+fn strip_codename<'a>(distro: &'a str, codename: Option<&str>) -> &'a str {
+    let distro = distro.trim_end();
+    let codename = match codename {
+        Some(codename) => codename,
+        None => return distro,
+    };
+
+    if distro.ends_with(')') {
+        let rest = &distro[..distro.len() - 1];
+
+        if let Some(open) = rest.rfind('(') {
+            if rest[open + 1..].trim().eq_ignore_ascii_case(codename) {
+                return rest[..open].trim_end();
+            }
+        }
+    }
+
+    distro
+}
+
+/// Get the version of the operating system kernel.
+///
+/// Example: "6.5.0-14-generic" (Linux) or "10.0.22631" (Windows)
+#[inline(always)]
+pub fn kernel_version() -> Result<String> {
+    Target::kernel_version(Os)
+}
+
+/// Get all of the system identification fields from `uname`, in one call.
+///
+/// Fails with [`ErrorKind::Other`](std::io::ErrorKind::Other) on
+/// systems without `uname`, such as Windows.
+#[inline(always)]
+pub fn uname_info() -> Result<UnameInfo> {
+    Target::uname_info(Os)
+}
+
+/// Get the machine hardware name, without mapping it to an
+/// [`Arch`](crate::Arch).
+///
+/// Example: "x86_64" or "aarch64"
+///
+/// This is `uname -m` where available, which avoids losing information about
+/// unusual machines; elsewhere it's the name of [`arch()`](crate::arch()).
+pub fn arch_str() -> Result<String> {
+    uname_info()
+        .map(|info| info.machine)
+        .or_else(|_| Target::arch(Os).map(|arch| arch.raw().into_owned()))
+}
+
+/// Get the network name of the device from `uname`.
+///
+/// This is usually the same as [`hostname()`], which uses `gethostname()`, but
+/// some systems truncate it to the fixed size of the `uname` field, and it may
+/// not be valid UTF-8 (in which case it's converted lossily).  Fails with
+/// [`ErrorKind::Other`](std::io::ErrorKind::Other) on systems
+/// without `uname`, such as Windows.
+#[inline(always)]
+pub fn nodename() -> Result<String> {
+    uname_info().map(|info| info.nodename)
+}
+
+/// Get the hardware model identifier.
+///
+/// Example: "MacBookPro18,3" or "Mac14,9"
+///
+/// Currently only supported on macOS; fails with
+/// [`ErrorKind::Other`](std::io::ErrorKind::Other) elsewhere.
+#[inline(always)]
+pub fn model_identifier() -> Result<String> {
+    Target::model_identifier(Os)
+}
+
+/// Get the hardware model's product name.
+///
+/// Example: "ThinkPad X1 Carbon Gen 11"
+///
+/// Currently only supported on Linux, where it's read from DMI; fails with
+/// [`ErrorKind::Other`](std::io::ErrorKind::Other) elsewhere.  Placeholders
+/// such as "To Be Filled By O.E.M." fail with
+/// [`ErrorKind::NotFound`](std::io::ErrorKind::NotFound).
+#[inline(always)]
+pub fn device_model() -> Result<String> {
+    Target::device_model(Os)
+}
+
+/// Get the hardware's manufacturer.
+///
+/// Example: "LENOVO"
+///
+/// Currently only supported on Linux, where it's read from DMI; fails with
+/// [`ErrorKind::Other`](std::io::ErrorKind::Other) elsewhere.  Placeholders
+/// such as "To Be Filled By O.E.M." fail with
+/// [`ErrorKind::NotFound`](std::io::ErrorKind::NotFound).
+#[inline(always)]
+pub fn device_vendor() -> Result<String> {
+    Target::device_vendor(Os)
+}
+
+/// Get the type of the filesystem the current working directory is on.
+///
+/// Example: "ext4", "apfs" or "overlay" (which usually means the program is
+/// running in a container)
+///
+/// Supported on Linux and Android (from `/proc/self/mountinfo`), and on macOS,
+/// FreeBSD, DragonFly BSD and OpenBSD (from `statfs()`); fails with
+/// [`ErrorKind::Other`](std::io::ErrorKind::Other) elsewhere.
+#[inline(always)]
+pub fn cwd_fs_type() -> Result<String> {
+    Target::cwd_fs_type(Os)
+}
+
+/// Get the identification fields of the operating system distribution, from a
+/// single read of `/etc/os-release`.
+///
+/// Fails with [`ErrorKind::Other`] on systems without `os-release`
+/// (such as macOS and Windows).
+#[inline(always)]
+pub fn distro_info() -> Result<DistroInfo> {
+    Target::distro_info(Os)
+}
+
 /// Get the device name (also known as "Pretty Name").
 ///
 /// Often used to identify device for bluetooth pairing.
@@ -63,15 +375,454 @@ pub fn devicename() -> Result<String> {
 /// Often used to identify device for bluetooth pairing.
 #[inline(always)]
 pub fn devicename_os() -> Result<OsString> {
+    #[cfg(feature = "mock-env")]
+    {
+        if let Some(devicename) = mock::var_os("DEVICENAME") {
+            return Ok(devicename);
+        }
+    }
+
     Target::devicename(Os)
 }
 
+/// Get one of the names of the host device.
+///
+/// [`NameKind::ComputerName`] is the same as [`devicename_os()`], and
+/// [`NameKind::HostName`] is the same as [`hostname()`].
+/// [`NameKind::LocalHostName`] is only supported on macOS; fails with
+/// [`ErrorKind::Other`](std::io::ErrorKind::Other) elsewhere.
+pub fn devicename_kind(kind: NameKind) -> Result<OsString> {
+    match kind {
+        NameKind::ComputerName => devicename_os(),
+        NameKind::HostName => hostname().map(OsString::from),
+        NameKind::LocalHostName => Target::local_hostname(Os),
+    }
+}
+
 /// Get the host device's hostname.
 ///
 /// Limited to a-z, A-Z, 0-9, and dashes.  This limit also applies to
 /// [`devicename()`] when targeting Windows.  Usually hostnames are
 /// case-insensitive, but it's not a hard requirement.
+///
+/// On unix-systems, if the hostname is empty (as in some minimal containers),
+/// the `HOSTNAME` environment variable is used instead.
 #[inline(always)]
 pub fn hostname() -> Result<String> {
+    #[cfg(feature = "mock-env")]
+    {
+        if let Some(hostname) = mock::var("HOSTNAME") {
+            return Ok(hostname);
+        }
+    }
+
     Target::hostname(Os)
 }
+
+/// Get the host device's hostname, with internationalized labels decoded from
+/// punycode (example: "xn--mnchen-3ya" becomes "münchen").
+///
+/// [`hostname()`] returns the raw ASCII form.  Fails with
+/// [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) if a label
+/// isn't valid punycode.
+///
+/// Requires the `idn` feature.
+#[cfg(feature = "idn")]
+pub fn hostname_unicode() -> Result<String> {
+    hostname_to_unicode(&hostname()?)
+}
+
+/// Decode the punycode labels of a hostname, leaving the others unchanged.
+#[cfg(feature = "idn")]
+fn hostname_to_unicode(hostname: &str) -> Result<String> {
+    let mut labels = Vec::new();
+
+    for label in hostname.split('.') {
+        labels.push(match label.get(..4) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("xn--") => {
+                conversions::punycode_decode(&label[4..]).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Invalid punycode label: {}", label),
+                    )
+                })?
+            }
+            _ => label.to_string(),
+        });
+    }
+
+    Ok(labels.join("."))
+}
+
+/// Get the host device's hostname, only if it's valid according to RFC 1123.
+///
+/// A valid hostname is at most 253 characters long, made of dot-separated
+/// labels of 1 to 63 ASCII letters, digits and hyphens, where no label starts
+/// or ends with a hyphen.  A trailing dot (as in the absolute domain name
+/// `host.example.com.`) isn't allowed.  Fails with
+/// [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) otherwise;
+/// [`hostname()`] returns the hostname whether or not it's valid.
+pub fn hostname_validated() -> Result<String> {
+    let hostname = hostname()?;
+
+    if !is_valid_hostname(&hostname) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid hostname: {:?}", hostname),
+        ));
+    }
+
+    Ok(hostname)
+}
+
+/// Check whether or not a hostname conforms to RFC 1123.
+fn is_valid_hostname(hostname: &str) -> bool {
+    hostname.len() <= 253
+        && hostname.split('.').all(|label| {
+            (1..=63).contains(&label.len())
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+}
+
+/// Get the host device's "pretty" hostname, falling back to its hostname.
+///
+/// On unix-systems, this is `PRETTY_HOSTNAME` from `/etc/machine-info` (as set
+/// by `hostnamectl`), which [`devicename_os()`] also uses on Linux.
+/// systemd-hostnamed isn't queried, since it reads the same file.  On other
+/// systems, it's the same as [`devicename_os()`].
+#[inline(always)]
+pub fn pretty_hostname() -> Result<OsString> {
+    Target::pretty_hostname(Os)
+}
+
+/// Get the host device's hostname, without the domain.
+///
+/// Everything after the first `.` is removed, such as the `.local` suffix that
+/// macOS often adds (`host.local` becomes `host`).
+pub fn hostname_short() -> Result<String> {
+    hostname().map(without_domain)
+}
+
+/// Remove everything after the first `.` in `hostname`.
+fn without_domain(mut hostname: String) -> String {
+    if let Some(dot) = hostname.find('.') {
+        hostname.truncate(dot);
+    }
+
+    hostname
+}
+
+/// Get the host device's fully qualified domain name from `/etc/hosts`.
+///
+/// The short hostname (see [`hostname_short()`]) is looked up in the hosts file
+/// without any DNS queries or other network I/O, so this works in sandboxes
+/// with no resolver.  The first name starting with `hostname.` on a line that
+/// lists the host is returned, example: `host.example.com` for the line
+/// `127.0.1.1 host.example.com host`.  Fails with
+/// [`ErrorKind::NotFound`](std::io::ErrorKind::NotFound) if there's no such
+/// name, and with
+/// [`ErrorKind::Other`](std::io::ErrorKind::Other) on non-unix
+/// systems.
+pub fn fqdn_local() -> Result<String> {
+    Target::fqdn_local(Os, &hostname_short()?)
+}
+
+/// Get the host device's hostname, cached after the first successful lookup.
+///
+/// Useful for logging-heavy code, but note that changes to the hostname while
+/// the process is running won't be reflected until [`invalidate_caches()`] is
+/// called.  Use [`hostname()`] for an up-to-date value.
+pub fn hostname_cached() -> Result<String> {
+    cached(&HOSTNAME, hostname)
+}
+
+/// Get the name of the operating system distribution and (possibly) version,
+/// cached after the first successful lookup.
+///
+/// [`distro()`] reads files on most systems, so this is cheaper when it's
+/// needed repeatedly, but an upgrade while the process is running won't be
+/// reflected until [`invalidate_caches()`] is called.
+pub fn distro_cached() -> Result<String> {
+    cached(&DISTRO, distro)
+}
+
+/// Clear the values cached by [`hostname_cached()`], [`distro_cached()`] and
+/// [`preferred_languages_cached()`](crate::preferred_languages_cached()), so
+/// that they're looked up again on next use.
+///
+/// Useful for long-running processes, or after changing `LANG`.
+pub fn invalidate_caches() {
+    for cache in &[&*HOSTNAME, &*DISTRO] {
+        *cache.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    *LANGUAGES.lock().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Run a potentially blocking lookup (such as [`hostname()`] or
+/// [`devicename()`]) on another thread, failing with [`ErrorKind::TimedOut`]
+/// if it takes longer than `timeout`.
+///
+/// On timeout, the lookup keeps running in the background until it finishes,
+/// and its result is discarded.  Fails if threads aren't supported.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// let hostname = whoami::fallible::with_timeout(
+///     whoami::fallible::hostname,
+///     Duration::from_secs(1),
+/// );
+/// ```
+pub fn with_timeout<T, F>(lookup: F, timeout: Duration) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(1);
+
+    thread::Builder::new().spawn(move || {
+        // The receiver is gone if timed out, so ignore the error
+        let _ = sender.send(lookup());
+    })?;
+
+    receiver.recv_timeout(timeout).map_err(|e| match e {
+        RecvTimeoutError::Timeout => {
+            Error::new(ErrorKind::TimedOut, "Lookup timed out")
+        }
+        RecvTimeoutError::Disconnected => {
+            Error::new(ErrorKind::Other, "Lookup panicked")
+        }
+    })?
+}
+
+lazy_static! {
+    static ref HOSTNAME: Mutex<Option<String>> = Mutex::new(None);
+    static ref DISTRO: Mutex<Option<String>> = Mutex::new(None);
+    pub(crate) static ref LANGUAGES: Mutex<Option<Vec<Language>>> =
+        Mutex::new(None);
+}
+
+/// Get the value in `cache`, or else look it up and cache it on success.
+fn cached(
+    cache: &Mutex<Option<String>>,
+    lookup: fn() -> Result<String>,
+) -> Result<String> {
+    let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if let Some(value) = cache.as_ref() {
+        return Ok(value.clone());
+    }
+
+    let value = lookup()?;
+
+    *cache = Some(value.clone());
+
+    Ok(value)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    lazy_static! {
+        /// Held by tests that depend on the contents of the global caches.
+        pub(crate) static ref CACHES: Mutex<()> = Mutex::new(());
+    }
+
+    #[test]
+    fn login_user_vars() {
+        let login_user = |vars: &[(&str, &str)]| {
+            let var = crate::tests::lookup(vars);
+
+            login_user_from(
+                |name| var(name).map(OsString::from),
+                || Ok(OsString::from("root")),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(login_user(&[("SUDO_USER", "jdoe")]), "jdoe");
+        assert_eq!(login_user(&[("DOAS_USER", "jdoe")]), "jdoe");
+        assert_eq!(
+            login_user(&[("SUDO_USER", "jdoe"), ("DOAS_USER", "other")]),
+            "jdoe",
+        );
+        assert_eq!(
+            login_user(&[("SUDO_USER", ""), ("DOAS_USER", "other")]),
+            "other",
+        );
+        assert_eq!(login_user(&[("SUDO_USER", "")]), "root");
+        assert_eq!(login_user(&[]), "root");
+    }
+
+    #[test]
+    fn cache() {
+        static LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+
+        fn lookup() -> Result<String> {
+            LOOKUPS.fetch_add(1, Ordering::SeqCst);
+            Ok("value".to_string())
+        }
+
+        fn fail() -> Result<String> {
+            Err(Error::new(ErrorKind::NotFound, "Not found"))
+        }
+
+        let cache = Mutex::new(None);
+
+        assert!(cached(&cache, fail).is_err());
+        assert_eq!(*cache.lock().unwrap(), None);
+        assert_eq!(cached(&cache, lookup).unwrap(), "value");
+        assert_eq!(cached(&cache, lookup).unwrap(), "value");
+        assert_eq!(cached(&cache, fail).unwrap(), "value");
+        assert_eq!(LOOKUPS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn invalidate() {
+        let _caches = CACHES.lock().unwrap_or_else(PoisonError::into_inner);
+
+        *HOSTNAME.lock().unwrap() = Some("cached-host".to_string());
+        *DISTRO.lock().unwrap() = Some("Cached Distro".to_string());
+
+        assert_eq!(hostname_cached().unwrap(), "cached-host");
+        assert_eq!(distro_cached().unwrap(), "Cached Distro");
+
+        invalidate_caches();
+
+        assert_eq!(*HOSTNAME.lock().unwrap(), None);
+        assert_eq!(*DISTRO.lock().unwrap(), None);
+        assert_eq!(hostname_cached().ok(), hostname().ok());
+        assert_eq!(distro_cached().ok(), distro().ok());
+    }
+
+    #[test]
+    fn valid_hostname() {
+        let label = "a".repeat(63);
+        let long = [label.as_str(); 4].join(".");
+
+        assert!(is_valid_hostname("localhost"));
+        assert!(is_valid_hostname("my-host.example.com"));
+        assert!(is_valid_hostname("123.example"));
+        assert!(is_valid_hostname(&label));
+        assert!(is_valid_hostname(&long[..253]));
+
+        assert!(!is_valid_hostname(""));
+        assert!(!is_valid_hostname(&format!("{}a", label)));
+        assert!(!is_valid_hostname(&long));
+        assert!(!is_valid_hostname("-host"));
+        assert!(!is_valid_hostname("host-"));
+        assert!(!is_valid_hostname("host.-example.com"));
+        assert!(!is_valid_hostname("host.example-.com"));
+        assert!(!is_valid_hostname("host.example.com."));
+        assert!(!is_valid_hostname("host..example.com"));
+        assert!(!is_valid_hostname("my_host"));
+        assert!(!is_valid_hostname("my host"));
+        assert!(!is_valid_hostname("hôte"));
+    }
+
+    #[test]
+    fn short_hostname() {
+        for (hostname, expected) in &[
+            ("host.local", "host"),
+            ("host", "host"),
+            ("host.example.com", "host"),
+            ("host.", "host"),
+            ("", ""),
+        ] {
+            assert_eq!(
+                without_domain(hostname.to_string()),
+                *expected,
+                "{:?}",
+                hostname,
+            );
+        }
+    }
+
+    #[test]
+    fn codename() {
+        assert_eq!(
+            strip_codename("Debian GNU/Linux 12 (bookworm)", Some("bookworm")),
+            "Debian GNU/Linux 12",
+        );
+        assert_eq!(
+            strip_codename("NixOS 23.11 (Tapir)", Some("tapir")),
+            "NixOS 23.11",
+        );
+        assert_eq!(
+            strip_codename("Ubuntu 22.04.3 LTS", Some("jammy")),
+            "Ubuntu 22.04.3 LTS",
+        );
+        assert_eq!(
+            strip_codename("Fedora Linux 39 (Workstation Edition)", None),
+            "Fedora Linux 39 (Workstation Edition)",
+        );
+        assert_eq!(
+            strip_codename(
+                "Fedora 26 (Workstation Edition)",
+                Some("twentysix"),
+            ),
+            "Fedora 26 (Workstation Edition)",
+        );
+        assert_eq!(
+            strip_codename("Debian GNU/Linux trixie/sid", Some("trixie")),
+            "Debian GNU/Linux trixie/sid",
+        );
+    }
+
+    #[cfg(feature = "idn")]
+    #[test]
+    fn unicode_hostname() {
+        let decode = |hostname| hostname_to_unicode(hostname).ok();
+
+        assert_eq!(decode("localhost").as_deref(), Some("localhost"));
+        assert_eq!(
+            decode("my-host.example.com").as_deref(),
+            Some("my-host.example.com"),
+        );
+        assert_eq!(
+            decode("xn--mnchen-3ya.example").as_deref(),
+            Some("münchen.example"),
+        );
+        assert_eq!(
+            decode("host.XN--bcher-kva.example").as_deref(),
+            Some("host.bücher.example"),
+        );
+        assert_eq!(
+            hostname_to_unicode("xn--mnchen-3y!.example")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData,
+        );
+    }
+    #[test]
+    fn timeout() {
+        let slow = with_timeout(
+            || {
+                thread::sleep(Duration::from_secs(2));
+                Ok(())
+            },
+            Duration::from_millis(10),
+        );
+
+        assert_eq!(slow.unwrap_err().kind(), ErrorKind::TimedOut);
+
+        let fast = with_timeout(|| Ok(42), Duration::from_secs(10));
+
+        assert_eq!(fast.unwrap(), 42);
+
+        let failed = with_timeout(
+            || -> Result<()> { Err(Error::new(ErrorKind::NotFound, "")) },
+            Duration::from_secs(10),
+        );
+
+        assert_eq!(failed.unwrap_err().kind(), ErrorKind::NotFound);
+    }
+}
@@ -70,14 +70,27 @@
 
 mod conversions;
 pub mod fallible;
+#[cfg(feature = "mock-env")]
+mod mock;
 mod os;
 
 use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    convert::Infallible,
+    env,
     ffi::OsString,
     fmt::{self, Display, Formatter},
+    fs,
+    hash::{Hash, Hasher},
     io::{Error, ErrorKind},
+    os::raw::c_int,
+    str::FromStr,
+    sync::{PoisonError, RwLock},
 };
 
+use lazy_static::lazy_static;
+
 use crate::os::{Os, Target};
 
 macro_rules! report_message {
@@ -88,6 +101,166 @@ macro_rules! report_message {
 
 const DEFAULT_USERNAME: &str = "Unknown";
 const DEFAULT_HOSTNAME: &str = "LocalHost";
+const DEFAULT_LANGUAGE: &str = "en-US";
+
+/// English names of common languages, sorted by ISO 639 code
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("af", "Afrikaans"),
+    ("am", "Amharic"),
+    ("ar", "Arabic"),
+    ("az", "Azerbaijani"),
+    ("be", "Belarusian"),
+    ("bg", "Bulgarian"),
+    ("bn", "Bengali"),
+    ("bs", "Bosnian"),
+    ("ca", "Catalan"),
+    ("cs", "Czech"),
+    ("cy", "Welsh"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("eo", "Esperanto"),
+    ("es", "Spanish"),
+    ("et", "Estonian"),
+    ("eu", "Basque"),
+    ("fa", "Persian"),
+    ("fi", "Finnish"),
+    ("fil", "Filipino"),
+    ("fr", "French"),
+    ("ga", "Irish"),
+    ("gl", "Galician"),
+    ("gu", "Gujarati"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("hr", "Croatian"),
+    ("hu", "Hungarian"),
+    ("hy", "Armenian"),
+    ("id", "Indonesian"),
+    ("is", "Icelandic"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ka", "Georgian"),
+    ("kk", "Kazakh"),
+    ("km", "Khmer"),
+    ("kn", "Kannada"),
+    ("ko", "Korean"),
+    ("lo", "Lao"),
+    ("lt", "Lithuanian"),
+    ("lv", "Latvian"),
+    ("mk", "Macedonian"),
+    ("ml", "Malayalam"),
+    ("mn", "Mongolian"),
+    ("mr", "Marathi"),
+    ("ms", "Malay"),
+    ("my", "Burmese"),
+    ("nb", "Norwegian Bokmål"),
+    ("ne", "Nepali"),
+    ("nl", "Dutch"),
+    ("nn", "Norwegian Nynorsk"),
+    ("no", "Norwegian"),
+    ("pa", "Punjabi"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("si", "Sinhala"),
+    ("sk", "Slovak"),
+    ("sl", "Slovenian"),
+    ("sq", "Albanian"),
+    ("sr", "Serbian"),
+    ("sv", "Swedish"),
+    ("sw", "Swahili"),
+    ("ta", "Tamil"),
+    ("te", "Telugu"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("ur", "Urdu"),
+    ("uz", "Uzbek"),
+    ("vi", "Vietnamese"),
+    ("zh", "Chinese"),
+    ("zu", "Zulu"),
+];
+
+/// Usual scripts of languages, sorted by ISO 639 code (Chinese is handled
+/// separately, since it depends on the region)
+const LANGUAGE_SCRIPTS: &[(&str, Script)] = &[
+    ("af", Script::Latin),
+    ("ar", Script::Arabic),
+    ("az", Script::Latin),
+    ("be", Script::Cyrillic),
+    ("bg", Script::Cyrillic),
+    ("bs", Script::Latin),
+    ("ca", Script::Latin),
+    ("cs", Script::Latin),
+    ("cy", Script::Latin),
+    ("da", Script::Latin),
+    ("de", Script::Latin),
+    ("el", Script::Greek),
+    ("en", Script::Latin),
+    ("eo", Script::Latin),
+    ("es", Script::Latin),
+    ("et", Script::Latin),
+    ("eu", Script::Latin),
+    ("fa", Script::Arabic),
+    ("fi", Script::Latin),
+    ("fil", Script::Latin),
+    ("fr", Script::Latin),
+    ("ga", Script::Latin),
+    ("gl", Script::Latin),
+    ("he", Script::Hebrew),
+    ("hi", Script::Devanagari),
+    ("hr", Script::Latin),
+    ("hu", Script::Latin),
+    ("id", Script::Latin),
+    ("is", Script::Latin),
+    ("it", Script::Latin),
+    ("ja", Script::Japanese),
+    ("kk", Script::Cyrillic),
+    ("ko", Script::Hangul),
+    ("lt", Script::Latin),
+    ("lv", Script::Latin),
+    ("mk", Script::Cyrillic),
+    ("mn", Script::Cyrillic),
+    ("mr", Script::Devanagari),
+    ("ms", Script::Latin),
+    ("nb", Script::Latin),
+    ("ne", Script::Devanagari),
+    ("nl", Script::Latin),
+    ("nn", Script::Latin),
+    ("no", Script::Latin),
+    ("pl", Script::Latin),
+    ("pt", Script::Latin),
+    ("ro", Script::Latin),
+    ("ru", Script::Cyrillic),
+    ("sk", Script::Latin),
+    ("sl", Script::Latin),
+    ("sq", Script::Latin),
+    ("sr", Script::Cyrillic),
+    ("sv", Script::Latin),
+    ("sw", Script::Latin),
+    ("th", Script::Thai),
+    ("tr", Script::Latin),
+    ("uk", Script::Cyrillic),
+    ("ur", Script::Arabic),
+    ("uz", Script::Latin),
+    ("vi", Script::Latin),
+    ("zu", Script::Latin),
+];
+
+/// Regions that don't use the metric system
+const IMPERIAL_REGIONS: &[&str] = &["LR", "MM", "US"];
+
+/// Regions that use a comma as the decimal separator
+const DECIMAL_COMMA_REGIONS: &[&str] = &[
+    "AD", "AL", "AM", "AO", "AR", "AT", "AZ", "BA", "BE", "BG", "BO", "BR",
+    "BY", "CL", "CM", "CO", "CR", "CU", "CY", "CZ", "DE", "DK", "DZ", "EC",
+    "EE", "ES", "FI", "FO", "FR", "GE", "GL", "GR", "HR", "HU", "ID", "IS",
+    "IT", "KZ", "LT", "LU", "LV", "MA", "MD", "ME", "MK", "MZ", "NL", "NO",
+    "PL", "PT", "PY", "RO", "RS", "RU", "SE", "SI", "SK", "SM", "TN", "TR",
+    "UA", "UY", "UZ", "VE", "VN", "ZA",
+];
 
 /// This crate's convenience type alias for [`Result`](std::result::Result)s
 pub type Result<T = (), E = Error> = std::result::Result<T, E>;
@@ -124,8 +297,11 @@ impl Display for Country {
 /// `en/US`).
 ///
 /// Uses <https://en.wikipedia.org/wiki/List_of_ISO_639-1_codes>
+///
+/// Languages are compared by their canonical tag, so the encoding of the locale
+/// a language came from doesn't matter (`en_US.UTF-8` and `en-US` are equal).
 #[non_exhaustive]
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Debug)]
 // #[allow(variant_size_differences)]
 pub enum Language {
     #[doc(hidden)]
@@ -146,6 +322,272 @@ impl Language {
             Self::En(country) | Self::Es(country) => *country,
         }
     }
+
+    /// Get the canonical tag for this language (example: `en-US`).
+    fn tag(&self) -> Cow<'_, str> {
+        let (code, country) = match self {
+            Self::__(code) => return Cow::Borrowed(code.as_str()),
+            Self::En(country) => ("en", country),
+            Self::Es(country) => ("es", country),
+        };
+
+        match country {
+            Country::Any => Cow::Borrowed(code),
+            country => Cow::Owned(format!("{}-{}", code, country)),
+        }
+    }
+
+    /// Get the English name of this language (example: "German" for `de-AT`).
+    ///
+    /// Only common languages are included; returns `None` for others.
+    pub fn display_name(&self) -> Option<&'static str> {
+        let tag = self.tag();
+        let code = tag.split(|c| c == '-' || c == '/').next()?;
+
+        LANGUAGE_NAMES
+            .binary_search_by(|(name_code, _)| (*name_code).cmp(code))
+            .ok()
+            .map(|index| LANGUAGE_NAMES[index].1)
+    }
+
+    /// Get the writing system of this language.
+    ///
+    /// This is the script subtag if there is one (example: `sr-Latn`), or else
+    /// the script the language is usually written in (example: Cyrillic for
+    /// `sr`).  For Chinese, the region decides between simplified and
+    /// traditional.  Returns `None` if unknown.
+    pub fn script(&self) -> Option<Script> {
+        let tag = self.tag();
+        let mut subtags = tag.split(|c| c == '-' || c == '/');
+        let code = subtags.next()?;
+
+        if let Some(script) = subtags.find(|subtag| {
+            subtag.len() == 4
+                && subtag.bytes().all(|byte| byte.is_ascii_alphabetic())
+        }) {
+            return Script::from_code(script);
+        }
+
+        if code == "zh" {
+            return Some(match self.region().as_deref() {
+                Some("HK") | Some("MO") | Some("TW") => Script::HanTraditional,
+                _ => Script::HanSimplified,
+            });
+        }
+
+        LANGUAGE_SCRIPTS
+            .binary_search_by(|(script_code, _)| (*script_code).cmp(code))
+            .ok()
+            .map(|index| LANGUAGE_SCRIPTS[index].1)
+    }
+
+    /// Get the region subtag (two letters or three digits), if any.
+    fn region(&self) -> Option<String> {
+        self.tag()
+            .split(|c| c == '-' || c == '/')
+            .skip(1)
+            .find(|subtag| {
+                (subtag.len() == 2
+                    && subtag.bytes().all(|b| b.is_ascii_alphabetic()))
+                    || (subtag.len() == 3
+                        && subtag.bytes().all(|b| b.is_ascii_digit()))
+            })
+            .map(ToString::to_string)
+    }
+}
+
+/// A locale, combining a language with the region and encoding to format for
+///
+/// Use [`ToString::to_string()`] to convert to a POSIX locale name (example:
+/// `de_DE.UTF-8`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale {
+    language: String,
+    region: Option<String>,
+    encoding: Option<String>,
+}
+
+impl Locale {
+    /// Get the locale from the `LC_ALL`, `LC_NUMERIC`, `LC_TIME`,
+    /// `LC_MONETARY` or `LANG` environment variable (the first that's set).
+    ///
+    /// `LC_MESSAGES` is skipped, since it's the language of messages rather
+    /// than the conventions to format numbers, dates and money for.  If the
+    /// locale is "C" (or "POSIX") or none are set, the user's most preferred
+    /// language is used instead, without an encoding.
+    pub fn from_env() -> Self {
+        Self::from_vars(|var| env::var(var).ok()).unwrap_or_else(|| {
+            let language = preferred_languages().swap_remove(0);
+
+            Self::from_language(&language, None)
+        })
+    }
+
+    /// Get the locale from the first set formatting variable, looked up with
+    /// `var`.  Returns `None` if none are set, or for the "C" locale.
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        ["LC_ALL", "LC_NUMERIC", "LC_TIME", "LC_MONETARY", "LANG"]
+            .iter()
+            .filter_map(|name| var(name))
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| Self::parse(&locale))
+    }
+
+    /// Parse a POSIX locale name (example: `de_DE.UTF-8@euro`), dropping the
+    /// modifier.  Returns `None` for the "C" and "POSIX" locales.
+    fn parse(locale: &str) -> Option<Self> {
+        let locale = locale.split('@').next()?;
+        let mut parts = locale.splitn(2, '.');
+        let tag = parts.next()?;
+        let encoding = parts.next().filter(|encoding| !encoding.is_empty());
+
+        if tag == "C" || tag == "POSIX" {
+            return None;
+        }
+
+        let language = conversions::language_from_locale(tag)?;
+
+        Some(Self::from_language(&language, encoding))
+    }
+
+    fn from_language(language: &Language, encoding: Option<&str>) -> Self {
+        let tag = language.tag();
+        let primary = tag
+            .split(|c| c == '-' || c == '/')
+            .next()
+            .unwrap_or_default();
+
+        Self {
+            language: primary.to_string(),
+            region: language.region(),
+            encoding: encoding.map(ToString::to_string),
+        }
+    }
+
+    /// Get the language code (example: `de`).
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Get the region code (example: `DE`), if any.
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// Get the character encoding (example: `UTF-8`), if any.
+    pub fn encoding(&self) -> Option<&str> {
+        self.encoding.as_deref()
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.language)?;
+
+        if let Some(region) = &self.region {
+            write!(f, "_{}", region)?;
+        }
+
+        if let Some(encoding) = &self.encoding {
+            write!(f, ".{}", encoding)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A writing system
+///
+/// Uses <https://en.wikipedia.org/wiki/ISO_15924>
+#[non_exhaustive]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Script {
+    /// `Latn`: Latin
+    Latin,
+    /// `Cyrl`: Cyrillic
+    Cyrillic,
+    /// `Grek`: Greek
+    Greek,
+    /// `Arab`: Arabic
+    Arabic,
+    /// `Hebr`: Hebrew
+    Hebrew,
+    /// `Deva`: Devanagari
+    Devanagari,
+    /// `Thai`: Thai
+    Thai,
+    /// `Hans`: Han (Simplified Chinese)
+    HanSimplified,
+    /// `Hant`: Han (Traditional Chinese)
+    HanTraditional,
+    /// `Jpan`: Japanese (Han, Hiragana and Katakana)
+    Japanese,
+    /// `Kore` / `Hang`: Korean (Hangul)
+    Hangul,
+}
+
+impl Script {
+    /// Get the script from an ISO 15924 code (example: `Cyrl`).
+    fn from_code(code: &str) -> Option<Self> {
+        Some(match code.to_ascii_lowercase().as_str() {
+            "latn" => Self::Latin,
+            "cyrl" => Self::Cyrillic,
+            "grek" => Self::Greek,
+            "arab" => Self::Arabic,
+            "hebr" => Self::Hebrew,
+            "deva" => Self::Devanagari,
+            "thai" => Self::Thai,
+            "hans" => Self::HanSimplified,
+            "hant" => Self::HanTraditional,
+            "jpan" => Self::Japanese,
+            "kore" | "hang" => Self::Hangul,
+            _ => return None,
+        })
+    }
+}
+
+impl Display for Script {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Latin => "Latin",
+            Self::Cyrillic => "Cyrillic",
+            Self::Greek => "Greek",
+            Self::Arabic => "Arabic",
+            Self::Hebrew => "Hebrew",
+            Self::Devanagari => "Devanagari",
+            Self::Thai => "Thai",
+            Self::HanSimplified => "Han (Simplified)",
+            Self::HanTraditional => "Han (Traditional)",
+            Self::Japanese => "Japanese",
+            Self::Hangul => "Hangul",
+        })
+    }
+}
+
+impl PartialEq for Language {
+    fn eq(&self, other: &Self) -> bool {
+        self.tag() == other.tag()
+    }
+}
+
+impl Eq for Language {}
+
+impl Hash for Language {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tag().hash(state)
+    }
+}
+
+impl PartialOrd for Language {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Language {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.tag().cmp(&other.tag())
+    }
 }
 
 impl Display for Language {
@@ -213,6 +655,17 @@ pub enum DesktopEnv {
     Ermine,
     /// Default desktop environment for Redox
     Orbital,
+    /// Ubuntu's desktop environment prior to 17.10, and Ubuntu Unity
+    Unity,
+    /// Default desktop environment on elementary OS
+    Pantheon,
+    /// Deepin Desktop Environment (DDE)
+    Deepin,
+    /// System76's desktop environment for Pop!_OS
+    Cosmic,
+    /// A standalone (usually tiling) window manager without a desktop
+    /// environment (example: "bspwm")
+    WindowManager(String),
     /// Unknown desktop environment
     Unknown(String),
 }
@@ -241,6 +694,11 @@ impl Display for DesktopEnv {
             Self::Ubuntu => "Ubuntu",
             Self::Ermine => "Ermine",
             Self::Orbital => "Orbital",
+            Self::Unity => "Unity",
+            Self::Pantheon => "Pantheon",
+            Self::Deepin => "Deepin",
+            Self::Cosmic => "COSMIC",
+            Self::WindowManager(a) => a,
             Self::Unknown(a) => a,
         })
     }
@@ -259,12 +717,18 @@ pub enum Platform {
     MacOS,
     Illumos,
     Ios,
+    TvOs,
+    WatchOs,
     Android,
     Nintendo,
     Xbox,
     PlayStation,
     Fuchsia,
     Redox,
+    /// Web Assembly running in a web browser
+    Wasm,
+    /// Web Assembly running in a WASI runtime (outside of a web browser)
+    Wasi,
     Unknown(String),
 }
 
@@ -281,17 +745,256 @@ impl Display for Platform {
             Self::MacOS => "Mac OS",
             Self::Illumos => "illumos",
             Self::Ios => "iOS",
+            Self::TvOs => "tvOS",
+            Self::WatchOs => "watchOS",
             Self::Android => "Android",
             Self::Nintendo => "Nintendo",
             Self::Xbox => "XBox",
             Self::PlayStation => "PlayStation",
             Self::Fuchsia => "Fuchsia",
             Self::Redox => "Redox",
+            Self::Wasm => "Web",
+            Self::Wasi => "WASI",
             Self::Unknown(a) => a,
         })
     }
 }
 
+impl FromStr for Platform {
+    type Err = Infallible;
+
+    /// Parse the [`Display`] representation of a [`Platform`], so that
+    /// `platform.to_string().parse::<Platform>()` always round-trips.
+    ///
+    /// Strings that aren't the name of a known platform parse as
+    /// [`Platform::Unknown`].
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.starts_with("Unknown: ") {
+            return Ok(Self::Unknown(s["Unknown: ".len()..].to_string()));
+        }
+
+        Ok(Self::ALL
+            .iter()
+            .find(|platform| platform.to_string() == s)
+            .cloned()
+            .unwrap_or_else(|| Self::Unknown(s.to_string())))
+    }
+}
+
+impl Platform {
+    /// Every known platform (all variants except [`Platform::Unknown`]).
+    pub const ALL: &'static [Platform] = &[
+        Platform::Linux,
+        Platform::Bsd,
+        Platform::Windows,
+        Platform::MacOS,
+        Platform::Illumos,
+        Platform::Ios,
+        Platform::TvOs,
+        Platform::WatchOs,
+        Platform::Android,
+        Platform::Nintendo,
+        Platform::Xbox,
+        Platform::PlayStation,
+        Platform::Fuchsia,
+        Platform::Redox,
+        Platform::Wasm,
+        Platform::Wasi,
+    ];
+
+    /// Get the broad family this platform belongs to.
+    pub fn family(&self) -> PlatformFamily {
+        match self {
+            Self::Linux
+            | Self::Bsd
+            | Self::MacOS
+            | Self::Illumos
+            | Self::Ios
+            | Self::TvOs
+            | Self::WatchOs
+            | Self::Android
+            | Self::Redox => PlatformFamily::Unix,
+            Self::Windows | Self::Xbox => PlatformFamily::Windows,
+            Self::Wasm | Self::Wasi => PlatformFamily::Wasm,
+            Self::Nintendo
+            | Self::PlayStation
+            | Self::Fuchsia
+            | Self::Unknown(_) => PlatformFamily::Other,
+        }
+    }
+
+    /// Get the lowercase name Rust uses for this platform in
+    /// `cfg!(target_os = "…")` (example: "macos" for [`Platform::MacOS`]).
+    ///
+    /// The mapping is approximate for platforms that group several targets:
+    /// [`Platform::Bsd`] is "freebsd", [`Platform::Xbox`] is "windows",
+    /// [`Platform::Nintendo`] is "horizon" and [`Platform::PlayStation`] is
+    /// "vita".  [`Platform::Wasm`] and [`Platform::Unknown`] are "unknown".
+    pub fn target_os_str(&self) -> &'static str {
+        match self {
+            Self::Linux => "linux",
+            Self::Bsd => "freebsd",
+            Self::Windows | Self::Xbox => "windows",
+            Self::MacOS => "macos",
+            Self::Illumos => "illumos",
+            Self::Ios => "ios",
+            Self::TvOs => "tvos",
+            Self::WatchOs => "watchos",
+            Self::Android => "android",
+            Self::Nintendo => "horizon",
+            Self::PlayStation => "vita",
+            Self::Fuchsia => "fuchsia",
+            Self::Redox => "redox",
+            Self::Wasi => "wasi",
+            Self::Wasm | Self::Unknown(_) => "unknown",
+        }
+    }
+
+    /// Get the platform name followed by its kernel version, for banners.
+    ///
+    /// Example: "Linux 6.5.0-14-generic"
+    ///
+    /// Returns `None` if this isn't the current [`platform()`], or if
+    /// [`fallible::kernel_version()`] fails.
+    pub fn version_string(&self) -> Option<String> {
+        if *self != platform() {
+            return None;
+        }
+
+        let version = fallible::kernel_version().ok()?;
+
+        Some(format!("{} {}", self, version))
+    }
+}
+
+/// A coarse grouping of [`Platform`]s
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum PlatformFamily {
+    /// Unix-like operating systems
+    Unix,
+    /// Windows and Windows-based systems
+    Windows,
+    /// Web Assembly environments
+    Wasm,
+    /// Anything else
+    Other,
+}
+
+impl Display for PlatformFamily {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Unix => "Unix",
+            Self::Windows => "Windows",
+            Self::Wasm => "Web Assembly",
+            Self::Other => "Other",
+        })
+    }
+}
+
+/// Which of the device's names to get, with [`fallible::devicename_kind()`]
+///
+/// These correspond to the three names macOS has for a computer.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum NameKind {
+    /// The user-friendly name (example: "Jane's MacBook Pro"), the same as
+    /// [`devicename()`]
+    ComputerName,
+    /// The network hostname (example: "janes-mbp.example.com"), the same as
+    /// [`fallible::hostname()`]
+    HostName,
+    /// The local network (Bonjour) hostname, without `.local` (example:
+    /// "Janes-MacBook-Pro")
+    LocalHostName,
+}
+
+/// A C standard library implementation, from [`libc()`]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum Libc {
+    /// The GNU C Library
+    Glibc,
+    /// musl libc (example: on Alpine Linux)
+    Musl,
+    /// Android's C library
+    Bionic,
+    /// Couldn't be determined, or not Linux
+    Unknown,
+}
+
+impl Display for Libc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Glibc => "glibc",
+            Self::Musl => "musl",
+            Self::Bionic => "Bionic",
+            Self::Unknown => "Unknown",
+        })
+    }
+}
+
+/// What a standard stream is connected to, from [`output_kind()`]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum OutputKind {
+    /// An interactive terminal
+    Terminal,
+    /// A pipe to another program (example: `program | less`)
+    Pipe,
+    /// A regular file (example: `program > out.txt`)
+    File,
+    /// Anything else, such as `/dev/null`, a socket or a closed stream
+    Redirected,
+}
+
+/// The class of a login session
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum SessionClass {
+    /// A regular user session
+    User,
+    /// The login screen of a display manager
+    Greeter,
+    /// A lock screen
+    LockScreen,
+    /// A session without a seat, such as for services
+    Background,
+}
+
+impl Display for SessionClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::User => "user",
+            Self::Greeter => "greeter",
+            Self::LockScreen => "lock-screen",
+            Self::Background => "background",
+        })
+    }
+}
+
+/// An application sandbox / packaging format the program is running in
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum Sandbox {
+    /// Snap (detected with `SNAP`)
+    Snap,
+    /// Flatpak (detected with `FLATPAK_ID` or `/.flatpak-info`)
+    Flatpak,
+    /// AppImage (detected with `APPIMAGE`)
+    AppImage,
+}
+
+impl Display for Sandbox {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Snap => "Snap",
+            Self::Flatpak => "Flatpak",
+            Self::AppImage => "AppImage",
+        })
+    }
+}
+
 /// The architecture of a CPU
 #[non_exhaustive]
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -304,6 +1007,12 @@ pub enum Arch {
     ArmV7,
     /// ARM64 (aarch64)
     Arm64,
+    /// ARM64EC (Windows on ARM)
+    ///
+    /// ARM64 code built with the x64-compatible "emulation compatible" ABI, so
+    /// that it can be mixed with emulated x64 code in the same process.  The
+    /// hardware is still [`Arch::Arm64`].
+    Arm64Ec,
     /// i386 (x86)
     I386,
     /// i586 (x86)
@@ -312,6 +1021,11 @@ pub enum Arch {
     I686,
     /// X86_64 / Amd64
     X64,
+    /// X32 (x86_64 with 32-bit pointers, Linux only)
+    ///
+    /// The operating system still reports [`Arch::X64`] for x32 programs, so
+    /// this is only returned by [`userland_arch()`].
+    X32,
     /// MIPS
     Mips,
     /// MIPS (LE)
@@ -322,6 +1036,8 @@ pub enum Arch {
     Mips64El,
     /// PowerPC
     PowerPc,
+    /// PowerPC (LE)
+    PowerPcLe,
     /// PowerPC64
     PowerPc64,
     /// PowerPC64LE
@@ -340,6 +1056,14 @@ pub enum Arch {
     Wasm32,
     /// 64-bit Web Assembly
     Wasm64,
+    /// Elbrus 2000
+    E2k,
+    /// Motorola 68000 series
+    M68k,
+    /// 128-bit RISC-V
+    Riscv128,
+    /// SuperH SH-4
+    Sh4,
     /// Unknown Architecture
     Unknown(String),
 }
@@ -350,11 +1074,128 @@ impl Display for Arch {
             f.write_str("Unknown: ")?;
         }
 
+        f.write_str(&self.raw())
+    }
+}
+
+impl FromStr for Arch {
+    type Err = Infallible;
+
+    /// Parse the [`Display`] representation of an [`Arch`], so that
+    /// `arch.to_string().parse::<Arch>()` always round-trips.
+    ///
+    /// Strings that aren't the canonical name of a known architecture parse as
+    /// [`Arch::Unknown`].
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.starts_with("Unknown: ") {
+            return Ok(Self::Unknown(s["Unknown: ".len()..].to_string()));
+        }
+
+        Ok(Self::ALL
+            .iter()
+            .find(|arch| arch.raw() == s)
+            .cloned()
+            .unwrap_or_else(|| Self::Unknown(s.to_string())))
+    }
+}
+
+/// A group of related [`Arch`]s, for [`Arch::is_arm()`] and friends
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum ArchFamily {
+    Arm,
+    X86,
+    Riscv,
+    Mips,
+    Power,
+}
+
+/// The address width of a CPU architecture
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum Width {
+    /// 32 bits
+    Bits32,
+    /// 64 bits
+    Bits64,
+    /// 128 bits
+    Bits128,
+}
+
+impl Display for Width {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Width::Bits32 => "32 bits",
+            Width::Bits64 => "64 bits",
+            Width::Bits128 => "128 bits",
+        })
+    }
+}
+
+/// The byte order of a CPU
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum Endianness {
+    /// Least significant byte first
+    Little,
+    /// Most significant byte first
+    Big,
+}
+
+impl Display for Endianness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
+            Endianness::Little => "Little Endian",
+            Endianness::Big => "Big Endian",
+        })
+    }
+}
+
+impl Arch {
+    /// Every known architecture (all variants except [`Arch::Unknown`]).
+    pub const ALL: &'static [Arch] = &[
+        Arch::ArmV5,
+        Arch::ArmV6,
+        Arch::ArmV7,
+        Arch::Arm64,
+        Arch::Arm64Ec,
+        Arch::I386,
+        Arch::I586,
+        Arch::I686,
+        Arch::X64,
+        Arch::X32,
+        Arch::Mips,
+        Arch::MipsEl,
+        Arch::Mips64,
+        Arch::Mips64El,
+        Arch::PowerPc,
+        Arch::PowerPcLe,
+        Arch::PowerPc64,
+        Arch::PowerPc64Le,
+        Arch::Riscv32,
+        Arch::Riscv64,
+        Arch::S390x,
+        Arch::Sparc,
+        Arch::Sparc64,
+        Arch::Wasm32,
+        Arch::Wasm64,
+        Arch::E2k,
+        Arch::M68k,
+        Arch::Riscv128,
+        Arch::Sh4,
+    ];
+
+    /// Get the machine string for this architecture.
+    ///
+    /// For [`Arch::Unknown`], this is the machine string as reported by the
+    /// operating system.  Otherwise, it's the canonical name of the
+    /// architecture (the [`Display`] representation).
+    pub fn raw(&self) -> Cow<'_, str> {
+        Cow::Borrowed(match self {
             Self::ArmV5 => "armv5",
             Self::ArmV6 => "armv6",
             Self::ArmV7 => "armv7",
             Self::Arm64 => "arm64",
+            Self::Arm64Ec => "arm64ec",
             Self::I386 => "i386",
             Self::I586 => "i586",
             Self::I686 => "i686",
@@ -363,6 +1204,7 @@ impl Display for Arch {
             Self::Mips64 => "mips64",
             Self::Mips64El => "mips64el",
             Self::PowerPc => "powerpc",
+            Self::PowerPcLe => "powerpcle",
             Self::PowerPc64 => "powerpc64",
             Self::PowerPc64Le => "powerpc64le",
             Self::Riscv32 => "riscv32",
@@ -373,31 +1215,121 @@ impl Display for Arch {
             Self::Wasm32 => "wasm32",
             Self::Wasm64 => "wasm64",
             Self::X64 => "x86_64",
+            Self::X32 => "x32",
+            Self::E2k => "e2k",
+            Self::M68k => "m68k",
+            Self::Riscv128 => "riscv128",
+            Self::Sh4 => "sh4",
             Self::Unknown(arch) => arch,
         })
     }
-}
 
-/// The address width of a CPU architecture
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-#[non_exhaustive]
-pub enum Width {
-    /// 32 bits
-    Bits32,
-    /// 64 bits
-    Bits64,
-}
+    /// Get the architecture component of a Rust target triple for this
+    /// architecture (example: `aarch64` for [`Arch::Arm64`]).
+    ///
+    /// RISC-V includes the extensions of Rust's Linux targets (`riscv64gc`
+    /// for [`Arch::Riscv64`]).  Returns `None` for [`Arch::Unknown`], and for
+    /// architectures that Rust doesn't have a target for.
+    pub fn triple_component(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::ArmV5 => "armv5te",
+            Self::ArmV6 => "arm",
+            Self::ArmV7 => "armv7",
+            Self::Arm64 => "aarch64",
+            Self::Arm64Ec => "arm64ec",
+            Self::I386 => "i386",
+            Self::I586 => "i586",
+            Self::I686 => "i686",
+            Self::X64 | Self::X32 => "x86_64",
+            Self::Mips => "mips",
+            Self::MipsEl => "mipsel",
+            Self::Mips64 => "mips64",
+            Self::Mips64El => "mips64el",
+            Self::PowerPc => "powerpc",
+            Self::PowerPc64 => "powerpc64",
+            Self::PowerPc64Le => "powerpc64le",
+            Self::Riscv32 => "riscv32gc",
+            Self::Riscv64 => "riscv64gc",
+            Self::S390x => "s390x",
+            Self::Sparc => "sparc",
+            Self::Sparc64 => "sparc64",
+            Self::Wasm32 => "wasm32",
+            Self::Wasm64 => "wasm64",
+            Self::M68k => "m68k",
+            Self::E2k
+            | Self::PowerPcLe
+            | Self::Riscv128
+            | Self::Sh4
+            | Self::Unknown(_) => return None,
+        })
+    }
 
-impl Display for Width {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.write_str(match self {
-            Width::Bits32 => "32 bits",
-            Width::Bits64 => "64 bits",
+    /// Check whether or not this is an ARM architecture (32-bit or 64-bit).
+    pub fn is_arm(&self) -> bool {
+        self.family() == Some(ArchFamily::Arm)
+    }
+
+    /// Check whether or not this is an x86 architecture (32-bit or 64-bit).
+    pub fn is_x86(&self) -> bool {
+        self.family() == Some(ArchFamily::X86)
+    }
+
+    /// Check whether or not this is a RISC-V architecture.
+    pub fn is_riscv(&self) -> bool {
+        self.family() == Some(ArchFamily::Riscv)
+    }
+
+    /// Check whether or not this is a MIPS architecture (either endianness).
+    pub fn is_mips(&self) -> bool {
+        self.family() == Some(ArchFamily::Mips)
+    }
+
+    /// Check whether or not this is a PowerPC architecture (either
+    /// endianness).
+    pub fn is_power(&self) -> bool {
+        self.family() == Some(ArchFamily::Power)
+    }
+
+    /// Get the family of this architecture, if it belongs to one.
+    fn family(&self) -> Option<ArchFamily> {
+        Some(match self {
+            Self::ArmV5
+            | Self::ArmV6
+            | Self::ArmV7
+            | Self::Arm64
+            | Self::Arm64Ec => ArchFamily::Arm,
+            Self::I386 | Self::I586 | Self::I686 | Self::X64 | Self::X32 => {
+                ArchFamily::X86
+            }
+            Self::Mips | Self::MipsEl | Self::Mips64 | Self::Mips64El => {
+                ArchFamily::Mips
+            }
+            Self::PowerPc
+            | Self::PowerPcLe
+            | Self::PowerPc64
+            | Self::PowerPc64Le => ArchFamily::Power,
+            Self::Riscv32 | Self::Riscv64 | Self::Riscv128 => ArchFamily::Riscv,
+            Self::S390x
+            | Self::Sparc
+            | Self::Sparc64
+            | Self::Wasm32
+            | Self::Wasm64
+            | Self::E2k
+            | Self::M68k
+            | Self::Sh4
+            | Self::Unknown(_) => return None,
         })
     }
-}
 
-impl Arch {
+    /// Get the machine string if this is an [`Arch::Unknown`].
+    pub fn as_unknown(&self) -> Option<&str> {
+        if let Self::Unknown(arch) = self {
+            Some(arch)
+        } else {
+            None
+        }
+    }
+
     /// Get the width of this architecture.
     pub fn width(&self) -> Result<Width> {
         match self {
@@ -410,10 +1342,15 @@ impl Arch {
             | Arch::Mips
             | Arch::MipsEl
             | Arch::PowerPc
+            | Arch::PowerPcLe
             | Arch::Riscv32
             | Arch::Sparc
-            | Arch::Wasm32 => Ok(Width::Bits32),
+            | Arch::Wasm32
+            | Arch::M68k
+            | Arch::Sh4
+            | Arch::X32 => Ok(Width::Bits32),
             Arch::Arm64
+            | Arch::Arm64Ec
             | Arch::Mips64
             | Arch::Mips64El
             | Arch::PowerPc64
@@ -422,7 +1359,9 @@ impl Arch {
             | Arch::S390x
             | Arch::Sparc64
             | Arch::Wasm64
-            | Arch::X64 => Ok(Width::Bits64),
+            | Arch::X64
+            | Arch::E2k => Ok(Width::Bits64),
+            Arch::Riscv128 => Ok(Width::Bits128),
             Arch::Unknown(unknown_arch) => Err(Error::new(
                 ErrorKind::InvalidData,
                 format!(
@@ -434,11 +1373,327 @@ impl Arch {
     }
 }
 
-/// Get the CPU Architecture.
-#[inline(always)]
-pub fn arch() -> Arch {
-    Target::arch(Os).expect(concat!("arch() failed.  ", report_message!()))
-}
+/// A snapshot of the user and environment, for diagnostics
+///
+/// Use [`Fingerprint::redacted()`] before logging it or sending it elsewhere,
+/// to avoid leaking personally identifiable information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Fingerprint {
+    /// The user's username, see [`username()`]
+    pub username: String,
+    /// The user's real (full) name, see [`realname()`]
+    pub realname: String,
+    /// The device's hostname, see [`fallible::hostname()`]
+    pub hostname: String,
+    /// The operating system distribution, see [`distro()`]
+    pub distro: String,
+    /// The platform, see [`platform()`]
+    pub platform: Platform,
+    /// The CPU architecture, see [`arch()`]
+    pub arch: Arch,
+}
+
+impl Fingerprint {
+    /// Replace the username, real name and hostname with hashes of them.
+    ///
+    /// The hash is stable, so the same machine and user always produces the
+    /// same tokens, but it's not cryptographic; don't rely on it to keep
+    /// values secret from a determined attacker.
+    pub fn redacted(&self) -> Self {
+        Self {
+            username: redact(&self.username),
+            realname: redact(&self.realname),
+            hostname: redact(&self.hostname),
+            ..self.clone()
+        }
+    }
+
+    /// Format each field as a "Label: value" line, followed by the CPU
+    /// architecture's width (see [`Arch::width()`]), or "Unknown" if it isn't
+    /// known.
+    ///
+    /// Example: "Username: ferris\nReal name: Ferris\n..."
+    pub fn display_all(&self) -> String {
+        format!(
+            "Username: {}\nReal name: {}\nHostname: {}\nDistro: {}\n\
+             Platform: {}\nArch: {}\nWidth: {}",
+            self.username,
+            self.realname,
+            self.hostname,
+            self.distro,
+            self.platform,
+            self.arch,
+            or_unknown(self.arch.width()),
+        )
+    }
+}
+
+/// A numeric user ID (UID)
+///
+/// See [`fallible::uid()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UserId(u32);
+
+impl UserId {
+    /// Check whether or not this is the superuser (UID 0).
+    pub fn is_root(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<u32> for UserId {
+    fn from(uid: u32) -> Self {
+        Self(uid)
+    }
+}
+
+impl From<UserId> for u32 {
+    fn from(uid: UserId) -> Self {
+        uid.0
+    }
+}
+
+impl Display for UserId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// A numeric group ID (GID)
+///
+/// See [`fallible::gid()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupId(u32);
+
+impl GroupId {
+    /// Check whether or not this is the superuser's group (GID 0, named "root"
+    /// or "wheel").
+    pub fn is_root(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<u32> for GroupId {
+    fn from(gid: u32) -> Self {
+        Self(gid)
+    }
+}
+
+impl From<GroupId> for u32 {
+    fn from(gid: GroupId) -> Self {
+        gid.0
+    }
+}
+
+impl Display for GroupId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// System identification, as reported by `uname`
+///
+/// See [`fallible::uname_info()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UnameInfo {
+    /// Name of the operating system implementation (example: "Linux")
+    pub sysname: String,
+    /// Network name of the device
+    pub nodename: String,
+    /// Release level of the operating system (example: "6.5.0-14-generic")
+    pub release: String,
+    /// Version level of the operating system
+    pub version: String,
+    /// Machine hardware name (example: "x86_64")
+    pub machine: String,
+    /// NIS or YP domain name of the device, if set (Linux and Android only)
+    pub domainname: Option<String>,
+}
+
+/// Identification of the operating system distribution, from `os-release`
+///
+/// See [`fallible::distro_info()`], and
+/// <https://www.freedesktop.org/software/systemd/man/os-release.html> for
+/// details of each field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DistroInfo {
+    /// Name for presentation to the user (example: "Fedora Linux 39
+    /// (Workstation Edition)")
+    pub pretty_name: Option<String>,
+    /// Name, without the version (example: "Fedora Linux")
+    pub name: Option<String>,
+    /// Lowercase identifier (example: "fedora")
+    pub id: Option<String>,
+    /// Lowercase version identifier (example: "39")
+    pub version_id: Option<String>,
+    /// Lowercase codename of the release, if it has one (example: "bookworm")
+    pub version_codename: Option<String>,
+    /// Identifier of the system image's build (example:
+    /// "23.11.20240115.b8dd8be" on NixOS)
+    pub build_id: Option<String>,
+    /// Identifiers of closely related distributions (example: `["debian"]`
+    /// for Ubuntu)
+    pub id_like: Vec<String>,
+    /// Homepage (example: "https://fedoraproject.org/")
+    pub home_url: Option<String>,
+}
+
+/// Hash a value using 64-bit FNV-1a, which (unlike the standard library's
+/// hasher) is guaranteed to be stable across Rust versions.
+fn redact(value: &str) -> String {
+    let hash = value.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+
+    format!("{:016x}", hash)
+}
+
+lazy_static! {
+    static ref ARCH_ALIASES: RwLock<Vec<(String, Arch)>> =
+        RwLock::new(Vec::new());
+}
+
+/// Map a machine hardware name (as reported by `uname -m`) to an [`Arch`],
+/// overriding whoami's built-in mapping.
+///
+/// Useful on hardware that whoami doesn't recognize yet.  Matching is
+/// case-insensitive, and registering the same machine name again replaces the
+/// previous alias.  Only used on unix-systems, where [`arch()`] is based on the
+/// machine hardware name.
+pub fn register_arch_alias(machine: &str, arch: Arch) {
+    let mut aliases =
+        ARCH_ALIASES.write().unwrap_or_else(PoisonError::into_inner);
+
+    aliases.retain(|(alias, _)| !alias.eq_ignore_ascii_case(machine));
+    aliases.push((machine.to_string(), arch));
+}
+
+/// Look up an alias registered with [`register_arch_alias()`].
+#[allow(dead_code)] // Not used on all targets
+pub(crate) fn arch_alias(machine: &str) -> Option<Arch> {
+    ARCH_ALIASES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(machine))
+        .map(|(_, arch)| arch.clone())
+}
+
+/// Get the C standard library of the operating system.
+///
+/// On Linux this is the libc the program was built for (`target_env`), or
+/// otherwise the one whose dynamic linker is installed (musl, if both musl's
+/// and glibc's are).  Only Linux and Android are supported; returns
+/// [`Libc::Unknown`] elsewhere.
+#[inline(always)]
+pub fn libc() -> Libc {
+    Target::libc(Os)
+}
+
+/// Get the CPU Architecture.
+///
+/// This is the architecture the operating system reports to the process, so
+/// an x64 program under Rosetta 2 on Apple silicon gets [`Arch::X64`]; use
+/// [`native_arch()`] for the hardware's.
+///
+/// With the `mock-env` feature, `WHOAMI_ARCH` is parsed (see [`Arch`]'s
+/// [`FromStr`] implementation) and returned instead if set, to test code paths
+/// for architectures without the hardware.
+#[inline(always)]
+pub fn arch() -> Arch {
+    #[cfg(feature = "mock-env")]
+    {
+        if let Some(arch) = mock::var("ARCH").and_then(|a| a.parse().ok()) {
+            return arch;
+        }
+    }
+
+    Target::arch(Os).expect(concat!("arch() failed.  ", report_message!()))
+}
+
+/// Get the CPU Architecture of the host.
+///
+/// This differs from [`arch()`] when the process is emulated, such as an x64
+/// program on Windows on ARM or under Rosetta 2 on macOS, where this returns
+/// [`Arch::Arm64`].  Falls back
+/// to [`arch()`] if the host's architecture can't be found.
+#[inline(always)]
+pub fn native_arch() -> Arch {
+    Target::native_arch(Os).unwrap_or_else(|_| arch())
+}
+
+/// Get the byte order of the CPU the program is running on.
+///
+/// This is the byte order the program was built for (`target_endian`), which
+/// bi-endian architectures (such as PowerPC64) always run the program with.
+#[inline(always)]
+pub fn endianness() -> Endianness {
+    if cfg!(target_endian = "big") {
+        Endianness::Big
+    } else {
+        Endianness::Little
+    }
+}
+
+/// Get the number of CPUs the program can actually use.
+///
+/// This can be less than the number of logical CPUs, most notably in
+/// containers.  On Linux it respects the CPU affinity mask and cgroup v1 / v2
+/// CPU quotas (rounded down, to at least 1), as seen from inside the cgroup.
+/// Elsewhere it's the number of online CPUs (`hardwareConcurrency` on the web).
+/// Falls back to the number of online CPUs if the usable ones can't be found,
+/// and returns 1 if neither can (such as on WASI).
+#[inline(always)]
+pub fn available_parallelism() -> usize {
+    Target::available_parallelism(Os)
+        .or_else(|| Target::cpu_count(Os))
+        .unwrap_or(1)
+}
+
+/// Get the CPU Architecture the program was built for.
+///
+/// This differs from [`arch()`] for a 32-bit program (or userland) on a 64-bit
+/// operating system, where [`arch()`] reports the operating system's
+/// architecture (example: [`Arch::X64`]) and this reports the program's
+/// (example: [`Arch::I686`]).  Unlike [`native_arch()`], it's never the
+/// hardware's architecture under emulation.  An ARM64EC program on Windows
+/// reports [`Arch::Arm64Ec`], and an x32 program on Linux (which the operating
+/// system can't tell apart from an x86_64 program) reports [`Arch::X32`].
+pub fn userland_arch() -> Arch {
+    reconcile_arch(arch(), env::consts::ARCH, cfg!(target_pointer_width = "32"))
+}
+
+/// Reconcile the operating system's `arch` with the `target_arch` (and
+/// whether or not it has 32-bit pointers) the program was built for.
+fn reconcile_arch(arch: Arch, target_arch: &str, pointer_32bit: bool) -> Arch {
+    if target_arch == "arm64ec" {
+        Arch::Arm64Ec
+    } else if target_arch == "x86_64" && pointer_32bit {
+        Arch::X32
+    } else if pointer_32bit {
+        arch_32bit(arch)
+    } else {
+        arch
+    }
+}
+
+/// Get the 32-bit counterpart of a 64-bit architecture.
+fn arch_32bit(arch: Arch) -> Arch {
+    match arch {
+        Arch::X64 => Arch::I686,
+        Arch::Arm64 => Arch::ArmV7,
+        Arch::Mips64 => Arch::Mips,
+        Arch::Mips64El => Arch::MipsEl,
+        Arch::PowerPc64 => Arch::PowerPc,
+        Arch::PowerPc64Le => Arch::PowerPcLe,
+        Arch::Riscv64 => Arch::Riscv32,
+        Arch::Sparc64 => Arch::Sparc,
+        arch => arch,
+    }
+}
 
 /// Get the user's username.
 ///
@@ -460,19 +1715,28 @@ pub fn username_os() -> OsString {
 }
 
 /// Get the user's real (full) name.
+///
+/// Falls back to the username if the real name is empty or isn't valid UTF-8;
+/// use [`fallible::realname()`] to detect this instead.
 #[inline(always)]
 pub fn realname() -> String {
     fallible::realname()
-        .or_else(|_| fallible::username())
-        .unwrap_or_else(|_| DEFAULT_USERNAME.to_owned())
+        .ok()
+        .filter(|realname| !realname.is_empty())
+        .or_else(|| fallible::username().ok())
+        .unwrap_or_else(|| DEFAULT_USERNAME.to_owned())
 }
 
 /// Get the user's real (full) name.
+///
+/// Falls back to the username if the real name is empty.
 #[inline(always)]
 pub fn realname_os() -> OsString {
     fallible::realname_os()
-        .or_else(|_| fallible::username_os())
-        .unwrap_or_else(|_| DEFAULT_USERNAME.to_owned().into())
+        .ok()
+        .filter(|realname| !realname.is_empty())
+        .or_else(|| fallible::username_os().ok())
+        .unwrap_or_else(|| DEFAULT_USERNAME.to_owned().into())
 }
 
 /// Get the device name (also known as "Pretty Name").
@@ -531,6 +1795,10 @@ pub fn hostname_os() -> OsString {
 /// Get the name of the operating system distribution and (possibly) version.
 ///
 /// Example: "Windows 10" or "Fedora 26 (Workstation Edition)"
+///
+/// Rolling release distributions (such as Arch Linux) may not have a version.
+/// On Gentoo, the Portage profile or baselayout release is added instead
+/// (example: "Gentoo Linux, profile default/linux/amd64/23.0/desktop").
 #[inline(always)]
 pub fn distro() -> String {
     fallible::distro().unwrap_or_else(|_| format!("Unknown {}", platform()))
@@ -550,17 +1818,70 @@ pub fn distro_os() -> OsString {
 /// Get the desktop environment.
 ///
 /// Example: "gnome" or "windows"
+///
+/// On unix-systems this is detected with the `DESKTOP_SESSION`,
+/// `XDG_CURRENT_DESKTOP`, `GDMSESSION` and `XDG_SESSION_DESKTOP` environment
+/// variables, in that order.  On macOS this is [`DesktopEnv::Aqua`], or
+/// `DesktopEnv::Unknown("headless")` when the program isn't running in a
+/// graphical login session (such as over SSH, or as a daemon).
 #[inline(always)]
 pub fn desktop_env() -> DesktopEnv {
     Target::desktop_env(Os)
 }
 
+/// Get the window manager (or Wayland compositor).
+///
+/// Unlike [`desktop_env()`], on X11 this can tell apart a desktop environment
+/// from the window manager it runs on.  Returns `None` when unavailable.
+///
+/// On X11 this is the `_NET_WM_NAME` of the window manager (example: "Mutter"
+/// under GNOME, or "i3"), which requires the `x11` feature.  On Wayland there's
+/// no equivalent, so this is the last entry of `XDG_CURRENT_DESKTOP` (example:
+/// "GNOME" rather than "Mutter", or "sway").
+#[inline(always)]
+pub fn window_manager() -> Option<String> {
+    Target::window_manager(Os)
+}
+
+/// Get the version of the desktop environment (or Wayland compositor).
+///
+/// Example: "1.9" (Sway) or "6" (KDE Plasma)
+///
+/// On unix-systems this is the git commit Hyprland was built from (from
+/// `HYPRLAND_INSTANCE_SIGNATURE`), or the major version of KDE Plasma (from
+/// `KDE_SESSION_VERSION`).  With the `compositor-ipc` feature, the release of
+/// Hyprland and Sway is queried by running `hyprctl` and `swaymsg`.  Returns
+/// `None` when unknown.
+#[inline(always)]
+pub fn desktop_env_version() -> Option<String> {
+    Target::desktop_env_version(Os)
+}
+
 /// Get the platform.
 #[inline(always)]
 pub fn platform() -> Platform {
     Target::platform(Os)
 }
 
+/// Get the platform, and whether or not it was positively identified.
+///
+/// When `false`, the [`Platform`] is a fallback (such as [`Platform::Linux`]
+/// for unrecognized unix-like targets, or [`Platform::Unknown`]) rather than
+/// the actual platform.
+#[inline(always)]
+pub fn platform_detected() -> (Platform, bool) {
+    (Target::platform(Os), Target::platform_detected(Os))
+}
+
+/// Get the platform's family.
+///
+/// Useful when only the broad kind of platform matters, rather than the
+/// specific [`Platform`].
+#[inline(always)]
+pub fn platform_family() -> PlatformFamily {
+    platform().family()
+}
+
 /// Get the user's preferred language(s).
 ///
 /// Returned as iterator of two letter language codes (lowercase), optionally
@@ -577,8 +1898,1178 @@ pub fn lang() -> impl Iterator<Item = String> {
 /// Returned as iterator of [`Language`]s wrapped in [`Result`]s.  The most
 /// preferred language is returned first, followed by next preferred, and so on.
 /// Unrecognized languages may return an error.
+///
+/// On unix-systems this follows GNU gettext, using the colon-separated list in
+/// `LANGUAGE` (unless the locale is "C"), or else the locale from `LC_ALL`,
+/// `LC_MESSAGES` or `LANG`.  Each language with a region is followed by the
+/// language without the region (example: `de-DE`, `de`).
+///
+/// Duplicates are removed, and the list is never empty; if no languages are
+/// found, it falls back to `en-US`.  With the `mock-env` feature, the
+/// colon-separated list in `WHOAMI_LANGS` is used instead if set.
 #[inline(always)]
 pub fn langs() -> impl Iterator<Item = Result<Language>> {
-    #[allow(deprecated)]
-    lang().map(|string| Ok(Language::__(Box::new(string))))
+    preferred_languages().into_iter().map(Ok)
+}
+
+/// Get the user's preferred languages, most preferred first.
+///
+/// This is the same list as [`langs()`], without the [`Result`]s, and is never
+/// empty; if no languages are found, it falls back to `en-US`.
+pub fn preferred_languages() -> Vec<Language> {
+    or_default_language(languages())
+}
+
+/// Get `languages`, or `en-US` if it's empty.
+fn or_default_language(languages: Vec<Language>) -> Vec<Language> {
+    if languages.is_empty() {
+        return vec![Language::__(Box::new(DEFAULT_LANGUAGE.to_string()))];
+    }
+
+    languages
+}
+
+/// Get the user's locale, for formatting.
+///
+/// Shorthand for [`Locale::from_env()`].
+#[inline(always)]
+pub fn locale() -> Locale {
+    Locale::from_env()
+}
+
+/// Get the user's preferred languages, cached after the first call.
+///
+/// This is the same list as [`preferred_languages()`], without reading the
+/// environment again on each call.  Changes to `LANG` (or the other locale
+/// variables) while the process is running won't be reflected until
+/// [`fallible::invalidate_caches()`] is called.
+pub fn preferred_languages_cached() -> Vec<Language> {
+    fallible::LANGUAGES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(preferred_languages)
+        .clone()
+}
+
+/// Get the user's preferred languages without duplicates, which may be empty.
+pub(crate) fn languages() -> Vec<Language> {
+    #[cfg(feature = "mock-env")]
+    let languages = match mock::var("LANGS") {
+        Some(list) => mock_languages(&list),
+        None => Target::langs(Os),
+    };
+    #[cfg(not(feature = "mock-env"))]
+    let languages = Target::langs(Os);
+
+    unique_languages(languages)
+}
+
+/// Parse the colon-separated list of locales in `WHOAMI_LANGS`.
+#[cfg(feature = "mock-env")]
+fn mock_languages(list: &str) -> Vec<Language> {
+    list.split(':')
+        .filter_map(conversions::language_from_locale)
+        .collect()
+}
+
+/// Remove the duplicates from `languages`, keeping the first of each.
+fn unique_languages(languages: Vec<Language>) -> Vec<Language> {
+    let mut unique = Vec::with_capacity(languages.len());
+
+    for language in languages {
+        if !unique.contains(&language) {
+            unique.push(language);
+        }
+    }
+
+    unique
+}
+
+/// Check whether or not the user's region uses the metric system.
+///
+/// This is a heuristic based on the region of the locale for measurements
+/// (`LC_MEASUREMENT`), or the user's most preferred language; it isn't based on
+/// full CLDR data.  Returns `true` if the region is unknown.
+pub fn uses_metric() -> bool {
+    region_uses_metric(locale_region("LC_MEASUREMENT").as_deref())
+}
+
+fn region_uses_metric(region: Option<&str>) -> bool {
+    region.map_or(true, |region| !IMPERIAL_REGIONS.contains(&region))
+}
+
+/// Get the decimal separator for the user's region (`.` or `,`).
+///
+/// This is a heuristic based on the region of the locale for numbers
+/// (`LC_NUMERIC`), or the user's most preferred language; it isn't based on
+/// full CLDR data.  Returns `.` if the region is unknown.
+pub fn decimal_separator() -> char {
+    region_decimal_separator(locale_region("LC_NUMERIC").as_deref())
+}
+
+fn region_decimal_separator(region: Option<&str>) -> char {
+    match region {
+        Some(region) if DECIMAL_COMMA_REGIONS.contains(&region) => ',',
+        _ => '.',
+    }
+}
+
+/// Get the region of the locale for a category (such as `LC_NUMERIC`) from the
+/// environment, falling back to the user's most preferred language.
+fn locale_region(category: &str) -> Option<String> {
+    ["LC_ALL", category, "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|locale| !locale.is_empty())
+        .and_then(|locale| conversions::language_from_locale(&locale))
+        .or_else(|| languages().into_iter().next())
+        .and_then(|language| language.region())
+}
+
+/// Check whether or not standard output is connected to a terminal.
+///
+/// Useful for deciding whether or not to print colored output.
+#[inline(always)]
+pub fn is_terminal() -> bool {
+    is_terminal_fd(1)
+}
+
+/// Check whether or not a file descriptor is connected to a terminal.
+///
+/// On Windows, only the standard streams (`0`, `1` and `2`) are supported.
+#[inline(always)]
+pub fn is_terminal_fd(fd: c_int) -> bool {
+    Target::isatty(Os, fd)
+}
+
+/// Get what standard output is connected to.
+///
+/// Unlike [`is_terminal()`], this tells a pipe to another program apart from a
+/// file.  Note that terminals on Windows that aren't consoles (such as MinTTY)
+/// are reported as [`OutputKind::Pipe`].
+#[inline(always)]
+pub fn output_kind() -> OutputKind {
+    Target::output_kind(Os, 1)
+}
+
+/// Get the terminal emulator's name, from environment variables it sets.
+///
+/// Values reported by the terminal itself (such as `TERM_PROGRAM`) are returned
+/// unchanged, falling back to the name of the terminal type from `TERM`.
+///
+/// Example: "iTerm.app", "Windows Terminal" or "xterm-256color"
+pub fn terminal() -> Option<String> {
+    terminal_from_vars(|name| env::var(name).ok())
+}
+
+/// Get the terminal emulator's name from environment variables looked up with
+/// `var`.
+fn terminal_from_vars(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let var = |name| var(name).filter(|value| !value.is_empty());
+
+    var("TERM_PROGRAM")
+        .or_else(|| var("TERMINAL_EMULATOR"))
+        .or_else(|| var("KONSOLE_VERSION").map(|_| "Konsole".to_string()))
+        .or_else(|| {
+            var("GNOME_TERMINAL_SERVICE").map(|_| "GNOME Terminal".to_string())
+        })
+        .or_else(|| var("WT_SESSION").map(|_| "Windows Terminal".to_string()))
+        .or_else(|| var("TERM"))
+}
+
+/// Get the class of the login session, from the `XDG_SESSION_CLASS`
+/// environment variable (set by systemd-logind).
+///
+/// Returns `None` when unset or unrecognized.
+pub fn session_class() -> Option<SessionClass> {
+    session_class_from(&env::var("XDG_SESSION_CLASS").ok()?)
+}
+
+/// Parse an `XDG_SESSION_CLASS` value.
+fn session_class_from(class: &str) -> Option<SessionClass> {
+    Some(match class {
+        "user" | "user-early" | "user-incomplete" => SessionClass::User,
+        "greeter" => SessionClass::Greeter,
+        "lock-screen" => SessionClass::LockScreen,
+        "background" | "background-light" => SessionClass::Background,
+        _ => return None,
+    })
+}
+
+/// Get the application sandbox the program is running in, from environment
+/// variables it sets (or the `/.flatpak-info` file, for Flatpak).
+///
+/// Returns `None` when not sandboxed.
+pub fn sandbox() -> Option<Sandbox> {
+    sandbox_from(
+        |name| {
+            env::var_os(name).map(|value| value.to_string_lossy().into_owned())
+        },
+        || fs::metadata("/.flatpak-info").is_ok(),
+    )
+}
+
+/// Get the application sandbox from environment variables looked up with
+/// `var`, and whether Flatpak's `/.flatpak-info` file exists.
+fn sandbox_from(
+    var: impl Fn(&str) -> Option<String>,
+    flatpak_info: impl FnOnce() -> bool,
+) -> Option<Sandbox> {
+    for (name, sandbox) in &[
+        ("SNAP", Sandbox::Snap),
+        ("FLATPAK_ID", Sandbox::Flatpak),
+        ("APPIMAGE", Sandbox::AppImage),
+    ] {
+        if var(name).map_or(false, |value| !value.is_empty()) {
+            return Some(*sandbox);
+        }
+    }
+
+    Some(Sandbox::Flatpak).filter(|_| flatpak_info())
+}
+
+/// Check whether or not the program is running in Cygwin on Windows.
+///
+/// Detected with the `OSTYPE` environment variable (if exported by the shell),
+/// or `/proc/version` for programs built for Cygwin.  Useful for adjusting path
+/// handling, since [`platform()`] may report either Windows or a Unix.
+pub fn is_cygwin() -> bool {
+    is_cygwin_from(|name| env::var(name).ok(), &proc_version())
+}
+
+/// Check for Cygwin with environment variables looked up with `var`, and the
+/// contents of `/proc/version`.
+fn is_cygwin_from(
+    var: impl Fn(&str) -> Option<String>,
+    proc_version: &str,
+) -> bool {
+    var("OSTYPE").map_or(false, |ostype| ostype.starts_with("cygwin"))
+        || proc_version.contains("CYGWIN_NT")
+}
+
+/// Check whether or not the program is running in MSYS2 on Windows.
+///
+/// Detected with the `MSYSTEM` environment variable (set in MSYS2 shells,
+/// including for native MinGW programs), the `OSTYPE` environment variable (if
+/// exported by the shell), or `/proc/version` for programs built for MSYS2.
+pub fn is_msys() -> bool {
+    is_msys_from(|name| env::var(name).ok(), &proc_version())
+}
+
+/// Check for MSYS2 with environment variables looked up with `var`, and the
+/// contents of `/proc/version`.
+fn is_msys_from(
+    var: impl Fn(&str) -> Option<String>,
+    proc_version: &str,
+) -> bool {
+    var("MSYSTEM").map_or(false, |msystem| !msystem.is_empty())
+        || var("OSTYPE").map_or(false, |ostype| ostype == "msys")
+        || proc_version.contains("MSYS_NT")
+}
+
+/// Read `/proc/version`, or an empty string if unavailable.
+fn proc_version() -> String {
+    fs::read_to_string("/proc/version").unwrap_or_default()
+}
+
+/// Display the value of a fallible getter, or "Unknown" if it failed.
+///
+/// Example: `or_unknown(fallible::hostname())`
+pub fn or_unknown<T: Display>(result: Result<T>) -> String {
+    match result {
+        Ok(value) => value.to_string(),
+        Err(_) => "Unknown".to_string(),
+    }
+}
+
+/// Get a snapshot of the user and environment.
+pub fn fingerprint() -> Fingerprint {
+    Fingerprint {
+        username: username(),
+        realname: realname(),
+        hostname: fallible::hostname()
+            .unwrap_or_else(|_| DEFAULT_HOSTNAME.to_string()),
+        distro: distro(),
+        platform: platform(),
+        arch: arch(),
+    }
+}
+
+/// Get a snapshot of the user and environment without personally identifiable
+/// information.
+///
+/// Shorthand for [`fingerprint()`] followed by [`Fingerprint::redacted()`].
+#[inline(always)]
+pub fn redacted_fingerprint() -> Fingerprint {
+    fingerprint().redacted()
+}
+
+/// Get labeled values describing the user and environment, for diagnostics.
+///
+/// The labels are always "User", "Host", "Distro", "Arch", "Platform" and
+/// "Desktop", in that order.  Values that can't be found are "Unknown" (see
+/// [`or_unknown()`]).  The hostname and distro are cached (see
+/// [`fallible::hostname_cached()`]).
+pub fn summary() -> Vec<(&'static str, String)> {
+    vec![
+        ("User", or_unknown(fallible::username())),
+        ("Host", or_unknown(fallible::hostname_cached())),
+        ("Distro", or_unknown(fallible::distro_cached())),
+        ("Arch", arch().to_string()),
+        ("Platform", platform().to_string()),
+        ("Desktop", desktop_env().to_string()),
+    ]
+}
+
+/// Get the version of this crate (example: "1.5.0"), for diagnostics.
+#[inline(always)]
+pub const fn lib_version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// Get the platform implied by `target_os`, for the operating systems that
+    /// are always identified.
+    fn target_platform() -> Option<Platform> {
+        Some(match env::consts::OS {
+            "linux" => Platform::Linux,
+            "macos" => Platform::MacOS,
+            "ios" => Platform::Ios,
+            "tvos" => Platform::TvOs,
+            "watchos" => Platform::WatchOs,
+            "windows" => Platform::Windows,
+            "illumos" => Platform::Illumos,
+            "freebsd" | "dragonfly" | "openbsd" | "netbsd" => Platform::Bsd,
+            "wasi" => Platform::Wasi,
+            _ => return None,
+        })
+    }
+
+    #[test]
+    fn platform_matches_target() {
+        if let Some(target) = target_platform() {
+            assert_eq!(platform(), target);
+        }
+    }
+
+    #[test]
+    fn platform_detected_on_target() {
+        let (detected_platform, detected) = platform_detected();
+
+        assert_eq!(detected_platform, platform());
+
+        if target_platform().is_some() {
+            assert!(detected, "{}", detected_platform);
+        }
+    }
+
+    #[test]
+    fn arch_matches_target() {
+        let arch = userland_arch();
+        let pointer_width = if cfg!(target_pointer_width = "32") {
+            Some(Width::Bits32)
+        } else if cfg!(target_pointer_width = "64") {
+            Some(Width::Bits64)
+        } else {
+            None
+        };
+
+        if let (Ok(width), Some(pointer_width)) = (arch.width(), pointer_width)
+        {
+            assert_eq!(width, pointer_width, "{}", arch);
+        }
+
+        if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+            assert!(arch.is_x86(), "{}", arch);
+        } else if cfg!(any(target_arch = "arm", target_arch = "aarch64")) {
+            assert!(arch.is_arm(), "{}", arch);
+        } else if cfg!(any(target_arch = "riscv32", target_arch = "riscv64")) {
+            assert!(arch.is_riscv(), "{}", arch);
+        }
+    }
+
+    #[test]
+    fn userland_arch_reconcile() {
+        for (arch, target_arch, pointer_32bit, expected) in &[
+            (Arch::X64, "x86_64", false, Arch::X64),
+            (Arch::X64, "x86", true, Arch::I686),
+            (Arch::I686, "x86", true, Arch::I686),
+            (Arch::Arm64, "aarch64", false, Arch::Arm64),
+            (Arch::Arm64, "arm", true, Arch::ArmV7),
+            (Arch::ArmV6, "arm", true, Arch::ArmV6),
+            (Arch::Riscv64, "riscv32", true, Arch::Riscv32),
+            (Arch::Arm64, "arm64ec", false, Arch::Arm64Ec),
+            (Arch::X64, "arm64ec", false, Arch::Arm64Ec),
+        ] {
+            assert_eq!(
+                reconcile_arch(arch.clone(), target_arch, *pointer_32bit),
+                *expected,
+                "{} {} {}",
+                arch,
+                target_arch,
+                pointer_32bit,
+            );
+        }
+    }
+
+    #[test]
+    fn arch_32bit_counterpart() {
+        for (arch, expected) in &[
+            (Arch::X64, Arch::I686),
+            (Arch::Arm64, Arch::ArmV7),
+            (Arch::Mips64, Arch::Mips),
+            (Arch::Mips64El, Arch::MipsEl),
+            (Arch::PowerPc64, Arch::PowerPc),
+            (Arch::PowerPc64Le, Arch::PowerPcLe),
+            (Arch::Riscv64, Arch::Riscv32),
+            (Arch::Sparc64, Arch::Sparc),
+            (Arch::I386, Arch::I386),
+            (Arch::ArmV7, Arch::ArmV7),
+            (Arch::X32, Arch::X32),
+            (Arch::Wasm32, Arch::Wasm32),
+        ] {
+            assert_eq!(arch_32bit(arch.clone()), *expected, "{}", arch);
+        }
+
+        // x32 runs on an x86_64 kernel, so `uname` can't tell it apart
+        for arch in &[Arch::X64, Arch::Unknown("x86_64".to_string())] {
+            assert_eq!(reconcile_arch(arch.clone(), "x86_64", true), Arch::X32);
+        }
+    }
+
+    #[test]
+    fn arch_families() {
+        let expected = [
+            (Arch::ArmV5, Some("arm")),
+            (Arch::ArmV6, Some("arm")),
+            (Arch::ArmV7, Some("arm")),
+            (Arch::Arm64, Some("arm")),
+            (Arch::Arm64Ec, Some("arm")),
+            (Arch::I386, Some("x86")),
+            (Arch::I586, Some("x86")),
+            (Arch::I686, Some("x86")),
+            (Arch::X64, Some("x86")),
+            (Arch::X32, Some("x86")),
+            (Arch::Mips, Some("mips")),
+            (Arch::MipsEl, Some("mips")),
+            (Arch::Mips64, Some("mips")),
+            (Arch::Mips64El, Some("mips")),
+            (Arch::PowerPc, Some("power")),
+            (Arch::PowerPcLe, Some("power")),
+            (Arch::PowerPc64, Some("power")),
+            (Arch::PowerPc64Le, Some("power")),
+            (Arch::Riscv32, Some("riscv")),
+            (Arch::Riscv64, Some("riscv")),
+            (Arch::Riscv128, Some("riscv")),
+            (Arch::S390x, None),
+            (Arch::Sparc, None),
+            (Arch::Sparc64, None),
+            (Arch::Wasm32, None),
+            (Arch::Wasm64, None),
+            (Arch::E2k, None),
+            (Arch::M68k, None),
+            (Arch::Sh4, None),
+            (Arch::Unknown("armv7".to_string()), None),
+        ];
+
+        assert_eq!(expected.len(), Arch::ALL.len() + 1);
+
+        for arch in Arch::ALL {
+            assert!(expected.iter().any(|(a, _)| a == arch), "{}", arch);
+        }
+
+        for (arch, family) in &expected {
+            let families: Vec<_> = [
+                ("arm", arch.is_arm()),
+                ("x86", arch.is_x86()),
+                ("riscv", arch.is_riscv()),
+                ("mips", arch.is_mips()),
+                ("power", arch.is_power()),
+            ]
+            .iter()
+            .filter(|(_, is_family)| *is_family)
+            .map(|(name, _)| *name)
+            .collect();
+
+            assert_eq!(
+                families,
+                family.iter().cloned().collect::<Vec<_>>(),
+                "{}",
+                arch,
+            );
+        }
+    }
+
+    #[test]
+    fn endianness_matches_target() {
+        if cfg!(target_endian = "little") {
+            assert_eq!(endianness(), Endianness::Little);
+        } else {
+            assert_eq!(endianness(), Endianness::Big);
+        }
+
+        // The runtime byte order agrees with the compile-time one
+        let little = 1u16.to_ne_bytes() == [1, 0];
+
+        assert_eq!(endianness() == Endianness::Little, little);
+        assert_eq!(Endianness::Little.to_string(), "Little Endian");
+        assert_eq!(Endianness::Big.to_string(), "Big Endian");
+    }
+
+    #[test]
+    fn arch_raw() {
+        for arch in Arch::ALL {
+            assert_eq!(arch.as_unknown(), None, "{}", arch);
+            assert_eq!(arch.raw(), arch.to_string(), "{}", arch);
+        }
+
+        assert_eq!(Arch::X64.raw(), "x86_64");
+        assert_eq!(Arch::Arm64.raw(), "arm64");
+
+        for machine in &["armv7l", "vax", ""] {
+            let arch = Arch::Unknown(machine.to_string());
+
+            assert_eq!(arch.as_unknown(), Some(*machine));
+            assert_eq!(arch.raw(), *machine);
+            assert_eq!(arch.to_string(), format!("Unknown: {}", machine));
+        }
+    }
+
+    #[test]
+    fn arch_round_trip() {
+        for arch in Arch::ALL {
+            assert_eq!(arch.to_string().parse::<Arch>(), Ok(arch.clone()));
+        }
+
+        let unknown = Arch::Unknown("armv7".to_string());
+
+        assert_eq!(unknown.to_string().parse::<Arch>(), Ok(unknown));
+    }
+
+    #[test]
+    fn triple_component() {
+        for (arch, triple) in &[
+            (Arch::ArmV5, "armv5te-unknown-linux-gnueabi"),
+            (Arch::ArmV6, "arm-unknown-linux-gnueabihf"),
+            (Arch::ArmV7, "armv7-unknown-linux-gnueabihf"),
+            (Arch::Arm64, "aarch64-unknown-linux-gnu"),
+            (Arch::Arm64Ec, "arm64ec-pc-windows-msvc"),
+            (Arch::I586, "i586-unknown-linux-gnu"),
+            (Arch::I686, "i686-pc-windows-msvc"),
+            (Arch::X64, "x86_64-unknown-linux-gnu"),
+            (Arch::X32, "x86_64-unknown-linux-gnux32"),
+            (Arch::Mips64El, "mips64el-unknown-linux-gnuabi64"),
+            (Arch::PowerPc64Le, "powerpc64le-unknown-linux-gnu"),
+            (Arch::Riscv32, "riscv32gc-unknown-linux-gnu"),
+            (Arch::Riscv64, "riscv64gc-unknown-linux-gnu"),
+            (Arch::S390x, "s390x-unknown-linux-gnu"),
+            (Arch::Sparc64, "sparc64-unknown-linux-gnu"),
+            (Arch::Wasm32, "wasm32-unknown-unknown"),
+            (Arch::M68k, "m68k-unknown-linux-gnu"),
+        ] {
+            let component = arch.triple_component().unwrap();
+
+            assert!(triple.starts_with(&format!("{}-", component)), "{}", arch);
+        }
+
+        for arch in &[
+            Arch::E2k,
+            Arch::PowerPcLe,
+            Arch::Riscv128,
+            Arch::Sh4,
+            Arch::Unknown("riscv64".to_string()),
+        ] {
+            assert_eq!(arch.triple_component(), None, "{:?}", arch);
+        }
+
+        // Rust calls every 32-bit x86 target "x86", and the rest of the host
+        // architecture names are a prefix of the triple's component
+        let host = userland_arch();
+
+        if let Some(component) = host.triple_component() {
+            if env::consts::ARCH != "x86" {
+                assert!(component.starts_with(env::consts::ARCH), "{}", host);
+            }
+        }
+    }
+
+    #[test]
+    fn language_eq() {
+        let locale = conversions::language_from_locale("en_US.UTF-8").unwrap();
+        let tag = conversions::language_from_locale("en-US").unwrap();
+        let languages: HashSet<Language> =
+            vec![locale.clone(), tag, Language::En(Country::Us)]
+                .into_iter()
+                .collect();
+
+        assert_eq!(languages.len(), 1, "{:?}", languages);
+        assert_eq!(locale, Language::En(Country::Us));
+        assert_eq!(
+            Language::En(Country::Any),
+            Language::__(Box::new("en".into()))
+        );
+        assert_eq!(Language::Es(Country::Us).cmp(&locale), Ordering::Greater);
+        assert_ne!(Language::En(Country::Us), Language::Es(Country::Us));
+    }
+
+    /// Look up environment variables in `vars` instead of the environment.
+    pub(crate) fn lookup<'a>(
+        vars: &'a [(&str, &str)],
+    ) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn language_display_name() {
+        for (locale, expected) in &[
+            ("de", Some("German")),
+            ("de_AT.UTF-8", Some("German")),
+            ("pt-BR", Some("Portuguese")),
+            ("zh-Hant-TW", Some("Chinese")),
+            ("en", Some("English")),
+            ("tlh", None),
+            ("xx_YY", None),
+        ] {
+            let language = conversions::language_from_locale(locale).unwrap();
+
+            assert_eq!(language.display_name(), *expected, "{}", locale);
+        }
+
+        assert_eq!(Language::En(Country::Us).display_name(), Some("English"));
+        assert_eq!(Language::Es(Country::Any).display_name(), Some("Spanish"));
+        assert!(LANGUAGE_NAMES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn language_script() {
+        for (locale, expected) in &[
+            ("zh-Hans", Some(Script::HanSimplified)),
+            ("zh_CN.UTF-8", Some(Script::HanSimplified)),
+            ("zh-TW", Some(Script::HanTraditional)),
+            ("zh_HK", Some(Script::HanTraditional)),
+            ("zh-Hant-CN", Some(Script::HanTraditional)),
+            ("zh", Some(Script::HanSimplified)),
+            ("sr-Cyrl", Some(Script::Cyrillic)),
+            ("sr-Latn", Some(Script::Latin)),
+            ("sr_RS@latin", Some(Script::Cyrillic)),
+            ("en", Some(Script::Latin)),
+            ("en_US.UTF-8", Some(Script::Latin)),
+            ("ja_JP", Some(Script::Japanese)),
+            ("en-Zzzz", None),
+            ("tlh", None),
+        ] {
+            let language = conversions::language_from_locale(locale).unwrap();
+
+            assert_eq!(language.script(), *expected, "{}", locale);
+        }
+
+        assert_eq!(Language::En(Country::Us).script(), Some(Script::Latin));
+        assert!(LANGUAGE_SCRIPTS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn preferred_languages_non_empty() {
+        let languages = preferred_languages();
+
+        assert!(!languages.is_empty());
+        assert_eq!(langs().map(Result::unwrap).collect::<Vec<_>>(), languages,);
+    }
+
+    #[cfg(feature = "mock-env")]
+    #[test]
+    fn mock_langs() {
+        let tags = |list| {
+            or_default_language(unique_languages(mock_languages(list)))
+                .into_iter()
+                .map(|language| language.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(tags("de_DE:fr"), ["de-DE", "fr"]);
+        assert_eq!(tags("de_DE.UTF-8:de-DE:fr:de_DE"), ["de-DE", "fr"]);
+        assert_eq!(tags("ja_JP::en"), ["ja-JP", "en"]);
+        assert_eq!(tags(""), ["en-US"]);
+        assert_eq!(tags("::"), ["en-US"]);
+    }
+
+    #[test]
+    fn preferred_languages_cache() {
+        let _caches = fallible::tests::CACHES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let cached =
+            vec![Language::Es(Country::Any), Language::En(Country::Us)];
+
+        *fallible::LANGUAGES.lock().unwrap() = Some(cached.clone());
+
+        assert_eq!(preferred_languages_cached(), cached);
+        assert_eq!(preferred_languages_cached(), cached);
+
+        fallible::invalidate_caches();
+
+        assert_eq!(*fallible::LANGUAGES.lock().unwrap(), None);
+        assert_eq!(preferred_languages_cached(), preferred_languages());
+        assert!(fallible::LANGUAGES.lock().unwrap().is_some());
+    }
+
+    #[test]
+    fn empty_langs() {
+        let default = or_default_language(Vec::new());
+
+        assert_eq!(default, [Language::En(Country::Us)]);
+        assert_eq!(default[0].to_string(), "en-US");
+        assert_eq!(unique_languages(Vec::new()), []);
+    }
+
+    #[test]
+    fn locale_parse() {
+        let locale = Locale::parse("de_DE.UTF-8").unwrap();
+
+        assert_eq!(locale.language(), "de");
+        assert_eq!(locale.region(), Some("DE"));
+        assert_eq!(locale.encoding(), Some("UTF-8"));
+        assert_eq!(locale.to_string(), "de_DE.UTF-8");
+
+        let locale = Locale::parse("en_US").unwrap();
+
+        assert_eq!(locale.language(), "en");
+        assert_eq!(locale.region(), Some("US"));
+        assert_eq!(locale.encoding(), None);
+        assert_eq!(locale.to_string(), "en_US");
+
+        assert_eq!(Locale::parse("C"), None);
+        assert_eq!(Locale::parse("C.UTF-8"), None);
+        assert_eq!(Locale::parse("POSIX"), None);
+    }
+
+    #[test]
+    fn locale_vars() {
+        for (vars, expected) in &[
+            (&[("LANG", "de_DE.UTF-8")][..], Some("de_DE.UTF-8")),
+            (
+                &[("LC_MESSAGES", "de_DE"), ("LANG", "en_US")],
+                Some("en_US"),
+            ),
+            (&[("LC_TIME", "fr_FR"), ("LANG", "en_US")], Some("fr_FR")),
+            (&[("LC_ALL", "C"), ("LC_NUMERIC", "de_DE")], None),
+            (
+                &[("LC_NUMERIC", ""), ("LC_MONETARY", "en_GB")],
+                Some("en_GB"),
+            ),
+            (&[("LC_MESSAGES", "de_DE")], None),
+        ] {
+            let locale = Locale::from_vars(lookup(vars));
+
+            assert_eq!(
+                locale.map(|locale| locale.to_string()).as_deref(),
+                *expected,
+                "{:?}",
+                vars,
+            );
+        }
+    }
+
+    #[test]
+    fn version_string() {
+        let current = platform();
+
+        if cfg!(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "windows",
+        )) {
+            let version = current.version_string().unwrap();
+
+            assert!(
+                version.starts_with(&format!("{} ", current)),
+                "{}",
+                version
+            );
+        }
+
+        for platform in Platform::ALL.iter().filter(|p| **p != current) {
+            assert_eq!(platform.version_string(), None, "{}", platform);
+        }
+    }
+
+    #[test]
+    fn uname_fields() {
+        if !cfg!(unix) {
+            assert!(fallible::uname_info().is_err());
+            return;
+        }
+
+        let info = fallible::uname_info().unwrap();
+
+        for (name, field) in &[
+            ("sysname", &info.sysname),
+            ("nodename", &info.nodename),
+            ("release", &info.release),
+            ("version", &info.version),
+            ("machine", &info.machine),
+        ] {
+            assert!(!field.is_empty(), "{}", name);
+        }
+
+        if let Some(domainname) = &info.domainname {
+            assert!(!domainname.is_empty());
+            assert_ne!(domainname, "(none)");
+        }
+
+        if cfg!(target_os = "linux") {
+            assert_eq!(info.sysname, "Linux");
+        } else if cfg!(target_os = "macos") {
+            assert_eq!(info.sysname, "Darwin");
+        }
+    }
+
+    #[test]
+    fn nodename_matches_hostname() {
+        if !cfg!(unix) {
+            assert!(fallible::nodename().is_err());
+            return;
+        }
+
+        let nodename = fallible::nodename().unwrap();
+
+        // Unless the hostname is unset (and taken from `HOSTNAME`), or too long
+        // for the `uname` field
+        if let Ok(hostname) = fallible::hostname() {
+            if !nodename.is_empty() && hostname.len() < 64 {
+                assert_eq!(nodename, hostname);
+            }
+        }
+    }
+
+    #[test]
+    fn arch_str_machine() {
+        let machine = fallible::arch_str().unwrap();
+
+        assert!(!machine.is_empty());
+
+        if cfg!(unix) {
+            assert_eq!(machine, fallible::uname_info().unwrap().machine);
+        } else {
+            assert_eq!(machine, Target::arch(Os).unwrap().raw());
+        }
+
+        if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+            assert_eq!(machine, "x86_64");
+        }
+    }
+
+    #[test]
+    fn lib_version_semver() {
+        const VERSION: &str = lib_version();
+
+        assert_eq!(VERSION, env!("CARGO_PKG_VERSION"));
+
+        // "major.minor.patch", optionally followed by "-pre" or "+build"
+        let core = VERSION.split(|c| c == '-' || c == '+').next().unwrap();
+        let parts: Vec<_> = core.split('.').collect();
+
+        assert_eq!(parts.len(), 3, "{}", VERSION);
+
+        for part in parts {
+            assert!(part.parse::<u64>().is_ok(), "{}", VERSION);
+        }
+    }
+
+    #[test]
+    fn login_name_or_not_found() {
+        // Daemons (and most CI runners) have no login record
+        match fallible::login_name() {
+            Ok(login_name) => assert!(!login_name.is_empty()),
+            Err(error) => {
+                assert!(cfg!(unix), "{}", error);
+                assert_eq!(error.kind(), ErrorKind::NotFound, "{}", error);
+            }
+        }
+    }
+
+    #[test]
+    fn terminal_fd() {
+        // Depends on how the tests are run, so only check it doesn't panic
+        assert_eq!(is_terminal(), is_terminal_fd(1));
+
+        assert!(!is_terminal_fd(-1));
+        assert!(!is_terminal_fd(9999));
+    }
+
+    #[test]
+    fn terminal_vars() {
+        for (vars, expected) in &[
+            (
+                &[("TERM_PROGRAM", "iTerm.app"), ("TERM", "xterm")][..],
+                Some("iTerm.app"),
+            ),
+            (
+                &[("TERMINAL_EMULATOR", "JetBrains-JediTerm")],
+                Some("JetBrains-JediTerm"),
+            ),
+            (
+                &[("KONSOLE_VERSION", "230805"), ("TERM", "xterm-256color")],
+                Some("Konsole"),
+            ),
+            (
+                &[("GNOME_TERMINAL_SERVICE", ":1.93")],
+                Some("GNOME Terminal"),
+            ),
+            (
+                &[("WT_SESSION", "0d5b1c7e"), ("TERM", "xterm-256color")],
+                Some("Windows Terminal"),
+            ),
+            (
+                &[("TERM_PROGRAM", ""), ("TERM", "xterm-256color")],
+                Some("xterm-256color"),
+            ),
+            (&[("TERM", "")], None),
+            (&[], None),
+        ] {
+            assert_eq!(
+                terminal_from_vars(lookup(vars)).as_deref(),
+                *expected,
+                "{:?}",
+                vars,
+            );
+        }
+    }
+
+    #[test]
+    fn session_classes() {
+        for (class, expected) in &[
+            ("user", Some(SessionClass::User)),
+            ("user-early", Some(SessionClass::User)),
+            ("user-incomplete", Some(SessionClass::User)),
+            ("greeter", Some(SessionClass::Greeter)),
+            ("lock-screen", Some(SessionClass::LockScreen)),
+            ("background", Some(SessionClass::Background)),
+            ("background-light", Some(SessionClass::Background)),
+            ("manager", None),
+            ("User", None),
+            ("", None),
+        ] {
+            assert_eq!(session_class_from(class), *expected, "{:?}", class);
+        }
+    }
+
+    #[test]
+    fn user_group_ids() {
+        for id in &[0, 1, 1000, u32::max_value()] {
+            let uid = UserId::from(*id);
+            let gid = GroupId::from(*id);
+
+            assert_eq!(u32::from(uid), *id);
+            assert_eq!(u32::from(gid), *id);
+            assert_eq!(uid.to_string(), id.to_string());
+            assert_eq!(gid.to_string(), id.to_string());
+            assert_eq!(uid.is_root(), *id == 0);
+            assert_eq!(gid.is_root(), *id == 0);
+        }
+
+        assert!(UserId::from(0) < UserId::from(1000));
+        assert_eq!(format!("{:>5}", UserId::from(42)), "   42");
+
+        assert_eq!(fallible::uid().is_ok(), cfg!(unix));
+        assert_eq!(fallible::gid().is_ok(), cfg!(unix));
+    }
+
+    #[test]
+    fn unknown_on_error() {
+        assert_eq!(or_unknown(Ok("crab")), "crab");
+        assert_eq!(or_unknown(Ok(Arch::X64)), "x86_64");
+        assert_eq!(or_unknown(Ok(42)), "42");
+        assert_eq!(or_unknown(Ok("")), "");
+        assert_eq!(
+            or_unknown::<String>(Err(Error::new(ErrorKind::NotFound, "Gone"))),
+            "Unknown",
+        );
+        assert_eq!(
+            or_unknown::<Width>(Err(Error::new(ErrorKind::Other, "Failed"))),
+            "Unknown",
+        );
+    }
+
+    #[test]
+    fn summary_labels() {
+        let _caches = fallible::tests::CACHES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let summary = summary();
+        let labels: Vec<_> = summary.iter().map(|(label, _)| *label).collect();
+
+        assert_eq!(
+            labels,
+            ["User", "Host", "Distro", "Arch", "Platform", "Desktop"],
+        );
+        assert_eq!(summary[3].1, arch().to_string());
+        assert_eq!(summary[4].1, platform().to_string());
+    }
+
+    #[test]
+    fn fingerprint_labels() {
+        let fingerprint = Fingerprint {
+            username: "ferris".to_string(),
+            realname: "Ferris".to_string(),
+            hostname: "crab".to_string(),
+            distro: "Fedora Linux 39".to_string(),
+            platform: Platform::Linux,
+            arch: Arch::X64,
+        };
+
+        assert_eq!(
+            fingerprint.display_all(),
+            "Username: ferris\nReal name: Ferris\nHostname: crab\n\
+             Distro: Fedora Linux 39\nPlatform: Linux\nArch: x86_64\n\
+             Width: 64 bits",
+        );
+
+        let redacted = fingerprint.redacted();
+        let labels: Vec<_> = redacted
+            .display_all()
+            .lines()
+            .map(|line| line.split(": ").next().unwrap().to_string())
+            .collect();
+
+        assert_eq!(
+            labels,
+            [
+                "Username",
+                "Real name",
+                "Hostname",
+                "Distro",
+                "Platform",
+                "Arch",
+                "Width",
+            ],
+        );
+        assert_ne!(redacted.username, fingerprint.username);
+        assert_eq!(redacted.distro, fingerprint.distro);
+    }
+
+    #[test]
+    fn sandbox_markers() {
+        for (vars, flatpak_info, expected) in &[
+            (
+                &[("SNAP", "/snap/hello/42")][..],
+                false,
+                Some(Sandbox::Snap),
+            ),
+            (
+                &[("FLATPAK_ID", "org.example.App")],
+                false,
+                Some(Sandbox::Flatpak),
+            ),
+            (&[], true, Some(Sandbox::Flatpak)),
+            (
+                &[("APPIMAGE", "/home/user/App.AppImage")],
+                false,
+                Some(Sandbox::AppImage),
+            ),
+            (&[("SNAP", "/snap/hello/42")], true, Some(Sandbox::Snap)),
+            (
+                &[("SNAP", ""), ("APPIMAGE", "/tmp/App.AppImage")],
+                false,
+                Some(Sandbox::AppImage),
+            ),
+            (&[("SNAP", "")], false, None),
+            (&[("container", "podman")], false, None),
+            (&[], false, None),
+        ] {
+            assert_eq!(
+                sandbox_from(lookup(vars), || *flatpak_info),
+                *expected,
+                "{:?}",
+                (vars, flatpak_info),
+            );
+        }
+    }
+
+    #[test]
+    fn cygwin_msys() {
+        let cygwin = "CYGWIN_NT-10.0-19045 version 3.4.10-1.x86_64 \
+            (runneradmin@fv-az1013-22) (gcc version 11.4.0 (GCC) ) 2023-11-29";
+        let msys = "MSYS_NT-10.0-19045 version 3.4.10.x86_64 \
+            (runneradmin@fv-az1013-22) (gcc version 13.2.0 (GCC) ) 2023-12-05";
+        let linux = "Linux version 6.5.0-14-generic (buildd@lcy02-amd64-031) \
+            (x86_64-linux-gnu-gcc-12 (Ubuntu 12.3.0-1ubuntu1~23.04) 12.3.0)";
+
+        for (vars, proc_version, expected) in &[
+            (&[("OSTYPE", "cygwin")][..], "", (true, false)),
+            (&[], cygwin, (true, false)),
+            (&[("MSYSTEM", "MINGW64")], "", (false, true)),
+            (
+                &[("MSYSTEM", "UCRT64"), ("OSTYPE", "msys")],
+                "",
+                (false, true),
+            ),
+            (&[("OSTYPE", "msys")], "", (false, true)),
+            (&[("MSYSTEM", "")], msys, (false, true)),
+            (&[("MSYSTEM", "")], "", (false, false)),
+            (&[("OSTYPE", "linux-gnu")], linux, (false, false)),
+            (&[], "", (false, false)),
+        ] {
+            assert_eq!(
+                (
+                    is_cygwin_from(lookup(vars), proc_version),
+                    is_msys_from(lookup(vars), proc_version),
+                ),
+                *expected,
+                "{:?}",
+                (vars, proc_version),
+            );
+        }
+    }
+
+    #[test]
+    fn region_conventions() {
+        for (locale, metric, separator) in &[
+            ("en_US.UTF-8", false, '.'),
+            ("de_DE.UTF-8", true, ','),
+            ("fr_FR", true, ','),
+            ("en_GB", true, '.'),
+            ("es_US", false, '.'),
+            ("my_MM", false, '.'),
+            ("en", true, '.'),
+        ] {
+            let region = conversions::language_from_locale(locale)
+                .and_then(|language| language.region());
+
+            assert_eq!(
+                region_uses_metric(region.as_deref()),
+                *metric,
+                "{}",
+                locale,
+            );
+            assert_eq!(
+                region_decimal_separator(region.as_deref()),
+                *separator,
+                "{}",
+                locale,
+            );
+        }
+    }
+
+    #[test]
+    fn platform_round_trip() {
+        for platform in Platform::ALL {
+            assert_eq!(
+                platform.to_string().parse::<Platform>(),
+                Ok(platform.clone()),
+            );
+        }
+
+        let unknown = Platform::Unknown("Linux".to_string());
+
+        assert_eq!(unknown.to_string().parse::<Platform>(), Ok(unknown));
+    }
 }
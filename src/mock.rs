@@ -0,0 +1,13 @@
+//! Environment variable overrides for testing, with the `mock-env` feature.
+
+use std::{env, ffi::OsString};
+
+/// Get the `WHOAMI_<FIELD>` override, if set.
+pub(crate) fn var_os(field: &str) -> Option<OsString> {
+    env::var_os(format!("WHOAMI_{}", field))
+}
+
+/// Get the `WHOAMI_<FIELD>` override, if set to valid unicode.
+pub(crate) fn var(field: &str) -> Option<String> {
+    env::var(format!("WHOAMI_{}", field)).ok()
+}
@@ -51,10 +51,25 @@
 )]
 mod target;
 
-use std::ffi::OsString;
+use std::{
+    ffi::OsString,
+    io::{Error, ErrorKind},
+    os::raw::c_int,
+};
 
 pub(crate) use self::target::*;
-use crate::{Arch, DesktopEnv, Language, Platform, Result};
+use crate::{
+    Arch, DesktopEnv, DistroInfo, GroupId, Language, Libc, OutputKind,
+    Platform, Result, UnameInfo, UserId,
+};
+
+/// Get the error for lookups that the target doesn't support.
+///
+/// `ErrorKind::Unsupported` is newer than the MSRV, so this is
+/// [`ErrorKind::Other`].
+pub(crate) fn unsupported() -> Error {
+    Error::new(ErrorKind::Other, "Unsupported")
+}
 
 /// Implement `Target for Os` to add platform support for a target.
 pub(crate) struct Os;
@@ -62,22 +77,71 @@ pub(crate) struct Os;
 /// Target platform support
 pub(crate) trait Target {
     /// Return a list of languages.
-    #[allow(dead_code)] // FIXME
     fn langs(self) -> Vec<Language>;
+    /// Return the most preferred language, without the rest of the list.
+    fn primary_lang(self) -> Option<Language>;
     /// Return the user's "real" / "full" name.
     fn realname(self) -> Result<OsString>;
     /// Return the user's username.
     fn username(self) -> Result<OsString>;
+    /// Return the user's username and "real" / "full" name together.
+    fn user(self) -> Result<(OsString, OsString)>;
+    /// Return the name of the user logged in on the controlling terminal.
+    fn login(self) -> Result<OsString>;
+    /// Return the effective user ID.
+    fn uid(self) -> Result<UserId>;
+    /// Return the effective group ID.
+    fn gid(self) -> Result<GroupId>;
     /// Return the computer's "fancy" / "pretty" name.
     fn devicename(self) -> Result<OsString>;
     /// Return the computer's hostname.
     fn hostname(self) -> Result<String>;
+    /// Return the computer's "pretty" hostname, or else its hostname.
+    fn pretty_hostname(self) -> Result<OsString>;
+    /// Return the computer's local network (Bonjour) hostname.
+    fn local_hostname(self) -> Result<OsString>;
     /// Return the OS distribution's name.
     fn distro(self) -> Result<String>;
+    /// Return the OS distribution's identification fields.
+    fn distro_info(self) -> Result<DistroInfo>;
+    /// Return the OS kernel's version.
+    fn kernel_version(self) -> Result<String>;
+    /// Return the raw system identification fields.
+    fn uname_info(self) -> Result<UnameInfo>;
+    /// Return the hardware model identifier.
+    fn model_identifier(self) -> Result<String>;
+    /// Return the hardware model's product name.
+    fn device_model(self) -> Result<String>;
+    /// Return the hardware's manufacturer.
+    fn device_vendor(self) -> Result<String>;
+    /// Return the type of the filesystem the working directory is on.
+    fn cwd_fs_type(self) -> Result<String>;
+    /// Return the fully qualified domain name of `hostname` from the hosts
+    /// file.
+    fn fqdn_local(self, hostname: &str) -> Result<String>;
     /// Return the desktop environment.
     fn desktop_env(self) -> DesktopEnv;
+    /// Return the window manager or Wayland compositor.
+    fn window_manager(self) -> Option<String>;
+    /// Return the version of the desktop environment or Wayland compositor.
+    fn desktop_env_version(self) -> Option<String>;
     /// Return the target platform.
     fn platform(self) -> Platform;
+    /// Return whether the target platform was positively identified, rather
+    /// than assumed.
+    fn platform_detected(self) -> bool;
     /// Return the computer's CPU architecture.
     fn arch(self) -> Result<Arch>;
+    /// Return the CPU architecture of the host, even if emulated.
+    fn native_arch(self) -> Result<Arch>;
+    /// Return whether or not a file descriptor is connected to a terminal.
+    fn isatty(self, fd: c_int) -> bool;
+    /// Return what a file descriptor is connected to.
+    fn output_kind(self, fd: c_int) -> OutputKind;
+    /// Return the operating system's C standard library.
+    fn libc(self) -> Libc;
+    /// Return the number of CPUs the program can use.
+    fn available_parallelism(self) -> Option<usize>;
+    /// Return the number of online logical CPUs.
+    fn cpu_count(self) -> Option<usize>;
 }
@@ -3,11 +3,13 @@
 #[cfg(not(any(target_pointer_width = "32", target_pointer_width = "64")))]
 compile_error!("Unexpected pointer width for target platform");
 
-use std::ffi::OsString;
+use std::{ffi::OsString, os::raw::c_int};
 
 use crate::{
-    os::{Os, Target},
-    Arch, DesktopEnv, Language, Platform, Result,
+    conversions,
+    os::{unsupported, Os, Target},
+    Arch, DesktopEnv, DistroInfo, GroupId, Language, Libc, OutputKind,
+    Platform, Result, UnameInfo, UserId,
 };
 
 #[inline(always)]
@@ -17,7 +19,13 @@ pub(crate) fn lang() -> impl Iterator<Item = String> {
 
 impl Target for Os {
     fn langs(self) -> Vec<Language> {
-        todo!()
+        lang()
+            .filter_map(|lang| conversions::language_from_locale(&lang))
+            .collect()
+    }
+
+    fn primary_lang(self) -> Option<Language> {
+        lang().find_map(|lang| conversions::language_from_locale(&lang))
     }
 
     #[inline(always)]
@@ -30,11 +38,41 @@ impl Target for Os {
         Ok("anonymous".to_string().into())
     }
 
+    #[inline(always)]
+    fn user(self) -> Result<(OsString, OsString)> {
+        Ok((self.username()?, Os.realname()?))
+    }
+
+    #[inline(always)]
+    fn login(self) -> Result<OsString> {
+        self.username()
+    }
+
+    #[inline(always)]
+    fn uid(self) -> Result<UserId> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn gid(self) -> Result<GroupId> {
+        Err(unsupported())
+    }
+
     #[inline(always)]
     fn devicename(self) -> Result<OsString> {
         Ok("Unknown".to_string().into())
     }
 
+    #[inline(always)]
+    fn pretty_hostname(self) -> Result<OsString> {
+        self.devicename()
+    }
+
+    #[inline(always)]
+    fn local_hostname(self) -> Result<OsString> {
+        Err(unsupported())
+    }
+
     #[inline(always)]
     fn hostname(self) -> Result<String> {
         Ok("localhost".to_string())
@@ -45,16 +83,71 @@ impl Target for Os {
         Ok("Emulated".to_string())
     }
 
+    #[inline(always)]
+    fn distro_info(self) -> Result<DistroInfo> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn kernel_version(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn uname_info(self) -> Result<UnameInfo> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn model_identifier(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn device_model(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn device_vendor(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn cwd_fs_type(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn fqdn_local(self, _hostname: &str) -> Result<String> {
+        Err(unsupported())
+    }
+
     #[inline(always)]
     fn desktop_env(self) -> DesktopEnv {
         DesktopEnv::Unknown("WebAssembly".to_string())
     }
 
+    #[inline(always)]
+    fn window_manager(self) -> Option<String> {
+        None
+    }
+
+    #[inline(always)]
+    fn desktop_env_version(self) -> Option<String> {
+        None
+    }
+
     #[inline(always)]
     fn platform(self) -> Platform {
         Platform::Unknown("Unknown".to_string())
     }
 
+    #[inline(always)]
+    fn platform_detected(self) -> bool {
+        false
+    }
+
     #[inline(always)]
     fn arch(self) -> Result<Arch> {
         Ok(if cfg!(target_pointer_width = "64") {
@@ -63,4 +156,34 @@ impl Target for Os {
             Arch::Wasm32
         })
     }
+
+    #[inline(always)]
+    fn native_arch(self) -> Result<Arch> {
+        self.arch()
+    }
+
+    #[inline(always)]
+    fn isatty(self, _fd: c_int) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn output_kind(self, _fd: c_int) -> OutputKind {
+        OutputKind::Redirected
+    }
+
+    #[inline(always)]
+    fn libc(self) -> Libc {
+        Libc::Unknown
+    }
+
+    #[inline(always)]
+    fn available_parallelism(self) -> Option<usize> {
+        None
+    }
+
+    #[inline(always)]
+    fn cpu_count(self) -> Option<usize> {
+        None
+    }
 }
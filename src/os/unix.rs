@@ -1,33 +1,68 @@
+#[cfg(target_os = "macos")]
+use std::os::{
+    raw::{c_long, c_uchar, c_ulong},
+    unix::ffi::OsStrExt,
+};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::path::Path;
+#[cfg(all(feature = "compositor-ipc", not(target_os = "macos")))]
+use std::process::Command;
 use std::{
+    convert::TryFrom,
     env,
-    ffi::OsString,
+    ffi::{OsStr, OsString},
     fs,
     io::{Error, ErrorKind},
     mem,
-    os::unix::ffi::OsStringExt,
-};
-#[cfg(target_os = "macos")]
-use std::{
     os::{
-        raw::{c_long, c_uchar, c_void},
-        unix::ffi::OsStrExt,
+        raw::{c_char, c_int},
+        unix::ffi::OsStringExt,
     },
-    ptr::null_mut,
 };
+#[cfg(any(
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd"
+))]
+use std::{ffi::CString, os::raw::c_void, ptr::null_mut};
 
+#[cfg(any(
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd"
+))]
+use nix::sys::statfs::statfs;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use nix::{
+    sched::{sched_getaffinity, CpuSet},
+    unistd::Pid,
+};
 use nix::{
-    sys::utsname::uname,
-    unistd::{gethostname, Uid, User},
+    sys::{
+        stat::{fstat, SFlag},
+        utsname::uname,
+    },
+    unistd::{gethostname, isatty, Gid, Uid, User},
 };
 
+#[cfg(not(target_os = "macos"))]
+use crate::conversions::{
+    distro_info_os_release, distro_os_release, join_name_version, release_value,
+};
 use crate::{
-    os::{Os, Target},
-    Arch, DesktopEnv, Language, Platform, Result,
+    conversions,
+    os::{unsupported, Os, Target},
+    Arch, DesktopEnv, DistroInfo, GroupId, Language, Libc, OutputKind,
+    Platform, Result, UnameInfo, UserId,
 };
 
 #[cfg(target_os = "macos")]
 #[link(name = "CoreFoundation", kind = "framework")]
 #[link(name = "SystemConfiguration", kind = "framework")]
+#[link(name = "CoreServices", kind = "framework")]
+#[link(name = "CoreGraphics", kind = "framework")]
 extern "system" {
     fn CFStringGetCString(
         the_string: *mut c_void,
@@ -44,23 +79,25 @@ extern "system" {
         store: *mut c_void,
         encoding: *mut u32,
     ) -> *mut c_void;
+    fn SCDynamicStoreCopyLocalHostName(store: *mut c_void) -> *mut c_void;
     fn CFRelease(cf: *const c_void);
-}
-
-enum Name {
-    User,
-    Real,
-}
-
-#[cfg(target_os = "macos")]
-unsafe fn strlen(cs: *const c_void) -> usize {
-    let mut len = 0;
-    let mut cs: *const u8 = cs.cast();
-    while *cs != 0 {
-        len += 1;
-        cs = cs.offset(1);
-    }
-    len
+    fn CFRetain(cf: *const c_void) -> *const c_void;
+    fn CFArrayGetCount(the_array: *mut c_void) -> c_long;
+    fn CFArrayGetValueAtIndex(
+        the_array: *mut c_void,
+        idx: c_long,
+    ) -> *const c_void;
+    fn CSIdentityQueryCreateForCurrentUser(
+        allocator: *mut c_void,
+    ) -> *mut c_void;
+    fn CSIdentityQueryExecute(
+        query: *mut c_void,
+        flags: c_ulong,
+        error: *mut *mut c_void,
+    ) -> c_uchar;
+    fn CSIdentityQueryCopyResults(query: *mut c_void) -> *mut c_void;
+    fn CSIdentityGetFullName(identity: *mut c_void) -> *mut c_void;
+    fn CGSessionCopyCurrentDictionary() -> *mut c_void;
 }
 
 #[cfg(target_os = "macos")]
@@ -73,42 +110,258 @@ fn os_from_cfstring(string: *mut c_void) -> OsString {
         let len = CFStringGetLength(string);
         let capacity =
             CFStringGetMaximumSizeForEncoding(len, 134_217_984 /* UTF8 */) + 1;
-        let mut out = Vec::with_capacity(capacity as usize);
-        if CFStringGetCString(
+        let mut out = vec![0u8; capacity as usize];
+        let success = CFStringGetCString(
             string,
             out.as_mut_ptr(),
             capacity,
             134_217_984, /* UTF8 */
-        ) != 0
-        {
-            out.set_len(strlen(out.as_ptr().cast())); // Remove trailing NUL byte
-            out.shrink_to_fit();
-            CFRelease(string);
-            OsString::from_vec(out)
-        } else {
-            CFRelease(string);
-            "".to_string().into()
+        ) != 0;
+
+        CFRelease(string);
+
+        if !success {
+            return "".to_string().into();
+        }
+
+        // Remove trailing NUL byte (searching only within the buffer)
+        let len = out.iter().position(|b| *b == 0).unwrap_or(out.len());
+
+        out.truncate(len);
+        out.shrink_to_fit();
+        OsString::from_vec(out)
+    }
+}
+
+/// Look up the current user's full name with Identity Services, which also
+/// covers network (Directory Services) accounts that have no gecos field.
+#[cfg(target_os = "macos")]
+fn identity_realname() -> Option<OsString> {
+    unsafe {
+        let query = CSIdentityQueryCreateForCurrentUser(null_mut());
+
+        if query.is_null() {
+            return None;
         }
+
+        let mut realname = None;
+
+        if CSIdentityQueryExecute(query, 0, null_mut()) != 0 {
+            let results = CSIdentityQueryCopyResults(query);
+
+            if !results.is_null() {
+                if CFArrayGetCount(results) > 0 {
+                    let identity = CFArrayGetValueAtIndex(results, 0);
+                    let name = CSIdentityGetFullName(identity as *mut c_void);
+
+                    if !name.is_null() {
+                        // Not owned by us, but `os_from_cfstring()` releases it
+                        CFRetain(name);
+                        realname = Some(os_from_cfstring(name));
+                    }
+                }
+
+                CFRelease(results);
+            }
+        }
+
+        CFRelease(query);
+        realname.filter(|name| !name.is_empty())
+    }
+}
+
+extern "C" {
+    fn getlogin_r(buf: *mut c_char, bufsize: usize) -> c_int;
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd"
+    ))]
+    fn sysctlbyname(
+        name: *const c_char,
+        oldp: *mut c_void,
+        oldlenp: *mut usize,
+        newp: *mut c_void,
+        newlen: usize,
+    ) -> c_int;
+}
+
+/// Read a string value with `sysctlbyname()`.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd"
+))]
+fn sysctl_string(name: &str) -> Result<String> {
+    let name = CString::new(name)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    let mut len = 0;
+
+    // Step 1. Get the size of the value
+    if unsafe {
+        sysctlbyname(name.as_ptr(), null_mut(), &mut len, null_mut(), 0)
+    } != 0
+    {
+        return Err(Error::last_os_error());
+    }
+
+    // Step 2. Get the value
+    let mut buf = vec![0u8; len];
+
+    if unsafe {
+        sysctlbyname(
+            name.as_ptr(),
+            buf.as_mut_ptr().cast(),
+            &mut len,
+            null_mut(),
+            0,
+        )
+    } != 0
+    {
+        return Err(Error::last_os_error());
+    }
+
+    // Step 3. Remove trailing NUL bytes, and convert to Rust String
+    buf.truncate(len);
+
+    while buf.last() == Some(&0) {
+        buf.pop();
+    }
+
+    String::from_utf8(buf).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Read an integer value with `sysctlbyname()`.
+/// Get the host's architecture from the process's `arch` and the value of
+/// `sysctl.proc_translated`, which is 1 under Rosetta 2, 0 for native
+/// processes, and missing on Intel Macs (before macOS 11).
+#[cfg(target_os = "macos")]
+fn rosetta_arch(arch: Arch, proc_translated: Option<c_int>) -> Arch {
+    // x86_64 processes translated by Rosetta 2 only run on Apple silicon
+    if proc_translated == Some(1) {
+        Arch::Arm64
+    } else {
+        arch
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn sysctl_int(name: &str) -> Result<c_int> {
+    let name = CString::new(name)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    let mut value: c_int = 0;
+    let mut len = mem::size_of::<c_int>();
+
+    if unsafe {
+        sysctlbyname(
+            name.as_ptr(),
+            (&mut value as *mut c_int).cast(),
+            &mut len,
+            null_mut(),
+            0,
+        )
+    } != 0
+    {
+        return Err(Error::last_os_error());
+    }
+
+    Ok(value)
+}
+
+fn getlogin() -> Result<OsString> {
+    // `LOGIN_NAME_MAX` is 256 on Linux, and smaller on other systems
+    let mut buf = vec![0u8; 256];
+    let errno = unsafe { getlogin_r(buf.as_mut_ptr().cast(), buf.len()) };
+
+    if errno != 0 {
+        // Usually no controlling terminal (daemons) or no login record
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            Error::from_raw_os_error(errno),
+        ));
+    }
+
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+
+    buf.truncate(len);
+
+    if buf.is_empty() {
+        return Err(Error::new(ErrorKind::NotFound, "Empty record"));
     }
+
+    Ok(OsString::from_vec(buf))
 }
 
 #[inline(always)]
-fn getpwuid(name: Name) -> Result<OsString> {
+/// Look up the effective user's username and real name.
+fn getpwuid() -> Result<(OsString, OsString)> {
     let user = User::from_uid(Uid::effective())?
         .ok_or_else(|| Error::new(ErrorKind::NotFound, "Null record"))?;
 
-    match name {
-        Name::User => Ok(OsString::from(user.name)),
-        Name::Real => {
-            // * The full user name is stored in the gecos field, which is
-            //   exposed by nix as a `CString` (C-style null-terminated string).
-            // * `CString::into_bytes` converts the string into a `Vec<u8>`
-            //   without the trailing null.
-            // * `OsString::from_vec`, only available on Unix, converts the
-            //   `Vec<u8>` into an `OsString`.
-            Ok(OsString::from_vec(user.gecos.into_bytes()))
+    // * The full user name is stored in the gecos field, which is exposed by
+    //   nix as a `CString` (C-style null-terminated string).
+    // * `CString::into_bytes` converts the string into a `Vec<u8>` without the
+    //   trailing null.
+    Ok((
+        OsString::from(user.name),
+        realname_from_gecos(user.gecos.into_bytes()),
+    ))
+}
+
+/// Get the full name at the start of a gecos field.
+fn realname_from_gecos(mut gecos: Vec<u8>) -> OsString {
+    let len = gecos_name_len(&gecos);
+
+    gecos.truncate(len);
+
+    // `OsString::from_vec`, only available on Unix, converts the `Vec<u8>` into
+    // an `OsString` without checking that it's valid UTF-8.
+    OsString::from_vec(gecos)
+}
+
+/// Fall back to other sources for the real name, if the gecos field is empty.
+fn realname_fallback(realname: OsString) -> OsString {
+    if !realname.is_empty() {
+        return realname;
+    }
+
+    // Network accounts on macOS may have an empty gecos field
+    #[cfg(target_os = "macos")]
+    {
+        if let Some(realname) = identity_realname() {
+            return realname;
+        }
+    }
+
+    // CI and minimal containers often have no gecos field, but may set these
+    for var in &["FULLNAME", "NAME"] {
+        if let Some(realname) =
+            env::var_os(var).filter(|realname| !realname.is_empty())
+        {
+            return realname;
         }
     }
+
+    realname
+}
+
+/// Get the length of the full name at the start of a gecos field.
+///
+/// The gecos field is traditionally `name,room,work phone,home phone,other`,
+/// but some systems (example: names imported from Active Directory) store a
+/// full name like `Doe, Jane` on its own.  Commas are only treated as
+/// separators if there are at least 4 fields, or if the last field is empty
+/// (example: `Jane,Room1,,`); otherwise the whole field is the name.
+fn gecos_name_len(gecos: &[u8]) -> usize {
+    let fields = gecos.split(|b| *b == b',').count();
+
+    if fields < 2 || (fields < 4 && gecos.last() != Some(&b',')) {
+        return gecos.len();
+    }
+
+    gecos.iter().position(|b| *b == b',').unwrap_or(gecos.len())
 }
 
 #[cfg(target_os = "macos")]
@@ -125,6 +378,10 @@ fn distro_xml(data: String) -> Result<String> {
                 let line = line.trim();
 
                 if line.starts_with("<key>") {
+                    // The previous key's value wasn't a `<string>`
+                    set_product_name = false;
+                    set_user_visible_version = false;
+
                     match line["<key>".len()..].trim_end_matches("</key>") {
                         "ProductName" => set_product_name = true,
                         "ProductUserVisibleVersion" => {
@@ -151,11 +408,158 @@ fn distro_xml(data: String) -> Result<String> {
                         );
                         set_user_visible_version = false;
                     }
+                } else if line.starts_with('<') {
+                    // Any other value type (`<data>`, `<array>`, `<true/>`…)
+                    set_product_name = false;
+                    set_user_visible_version = false;
                 }
             }
         }
     }
 
+    distro_product(product_name, user_visible_version)
+}
+
+/// Parse the top-level dictionary of a binary property list (`bplist00`),
+/// keeping only the entries where both the key and the value are strings.
+#[cfg(target_os = "macos")]
+fn bplist_strings(data: &[u8]) -> Option<Vec<(String, String)>> {
+    fn uint(bytes: &[u8]) -> Option<usize> {
+        if bytes.len() > 8 {
+            return None;
+        }
+
+        let value = bytes
+            .iter()
+            .fold(0u64, |value, byte| value << 8 | u64::from(*byte));
+
+        usize::try_from(value).ok()
+    }
+
+    fn slice(data: &[u8], start: usize, len: usize) -> Option<&[u8]> {
+        data.get(start..start.checked_add(len)?)
+    }
+
+    let trailer = slice(data, data.len().checked_sub(32)?, 32)?;
+    let offset_size = usize::from(trailer[6]);
+    let ref_size = usize::from(trailer[7]);
+    let num_objects = uint(&trailer[8..16])?;
+    let top_object = uint(&trailer[16..24])?;
+    let offset_table = uint(&trailer[24..32])?;
+
+    // Get the position of an object from its index in the offset table
+    let object = |index: usize| {
+        if index >= num_objects {
+            return None;
+        }
+
+        let start =
+            offset_table.checked_add(index.checked_mul(offset_size)?)?;
+
+        uint(slice(data, start, offset_size)?)
+    };
+    // Get the type, length and position of the contents of an object
+    let header = |offset: usize| {
+        let marker = *data.get(offset)?;
+
+        if marker & 0x0F != 0x0F {
+            return Some((marker >> 4, usize::from(marker & 0x0F), offset + 1));
+        }
+
+        // The length is stored in a following integer object
+        let int_marker = *data.get(offset + 1)?;
+
+        if int_marker >> 4 != 0x1 {
+            return None;
+        }
+
+        let size = 1usize.checked_shl(u32::from(int_marker & 0x0F))?;
+        let len = uint(slice(data, offset + 2, size)?)?;
+
+        Some((marker >> 4, len, offset + 2 + size))
+    };
+    let string = |index: usize| {
+        let (kind, len, start) = header(object(index)?)?;
+
+        match kind {
+            // ASCII
+            0x5 => String::from_utf8(slice(data, start, len)?.to_vec()).ok(),
+            // UTF-16 (big-endian)
+            0x6 => {
+                let units: Vec<u16> = slice(data, start, len.checked_mul(2)?)?
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                    .collect();
+
+                String::from_utf16(&units).ok()
+            }
+            _ => None,
+        }
+    };
+    let (kind, len, start) = header(object(top_object)?)?;
+
+    // Not a dictionary
+    if kind != 0xD {
+        return None;
+    }
+
+    // The key references are followed by the value references
+    let reference = |index: usize| {
+        uint(slice(
+            data,
+            start.checked_add(index.checked_mul(ref_size)?)?,
+            ref_size,
+        )?)
+    };
+    let mut entries = Vec::new();
+
+    for index in 0..len {
+        let key = string(reference(index)?);
+        let value = string(reference(len.checked_add(index)?)?);
+
+        if let (Some(key), Some(value)) = (key, value) {
+            entries.push((key, value));
+        }
+    }
+
+    Some(entries)
+}
+
+#[cfg(target_os = "macos")]
+fn distro_bplist(data: &[u8]) -> Result<String> {
+    let entries = bplist_strings(data)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Parsing failed"))?;
+    let value = |key: &str| {
+        entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    };
+
+    distro_product(
+        value("ProductName"),
+        value("ProductUserVisibleVersion").or_else(|| value("ProductVersion")),
+    )
+}
+
+/// Read a `SystemVersion.plist`, in either the XML or the binary format.
+#[cfg(target_os = "macos")]
+fn distro_plist(data: Vec<u8>) -> Result<String> {
+    if data.starts_with(b"bplist00") {
+        return distro_bplist(&data);
+    }
+
+    let data = String::from_utf8(data)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+    distro_xml(data)
+}
+
+#[cfg(target_os = "macos")]
+fn distro_product(
+    product_name: Option<&str>,
+    user_visible_version: Option<&str>,
+) -> Result<String> {
     Ok(if let Some(product_name) = product_name {
         if let Some(user_visible_version) = user_visible_version {
             format!("{} {}", product_name, user_visible_version)
@@ -194,20 +598,95 @@ impl Iterator for LangIter {
     }
 }
 
+const DEFAULT_LANG: &str = "en_US";
+
+/// Check if a locale is the "C" (or "POSIX") locale, meaning no language
+/// preference.
+fn is_c_locale(locale: &str) -> bool {
+    let locale = locale.split('.').next().unwrap_or_default();
+
+    locale == "C" || locale == "POSIX"
+}
+
+/// Convert a colon-separated list of locales (as found in `LANGUAGE`) into an
+/// ordered, deduplicated list of languages.
+///
+/// Like GNU gettext, each locale with a region is followed by the language
+/// without the region (`de_DE` becomes `de-DE`, `de`).  "C" and "POSIX"
+/// entries are skipped.
+fn langs_from_list(list: &str) -> Vec<Language> {
+    let mut langs = Vec::new();
+
+    for language in list
+        .split(':')
+        .filter(|locale| !is_c_locale(locale))
+        .filter_map(conversions::language_from_locale)
+    {
+        let tag = language.to_string();
+        let fallback = tag
+            .split('-')
+            .next()
+            .filter(|fallback| *fallback != tag)
+            .map(|fallback| Language::__(Box::new(fallback.to_string())));
+
+        for language in Some(language).into_iter().chain(fallback) {
+            if !langs.contains(&language) {
+                langs.push(language);
+            }
+        }
+    }
+
+    langs
+}
+
+/// Get the first language in a colon-separated list of locales, without
+/// building the whole list.
+fn first_lang_from_list(list: &str) -> Option<Language> {
+    list.split(':')
+        .filter(|locale| !is_c_locale(locale))
+        .find_map(conversions::language_from_locale)
+}
+
+/// Get the colon-separated list of locales to get the languages from.
+///
+/// Follows GNU gettext, where `LANGUAGE` is only used if the locale (from
+/// `LC_ALL`, `LC_MESSAGES` or `LANG`) isn't "C".  Returns `None` for the "C"
+/// locale.
+fn language_list() -> Option<String> {
+    language_list_from(|name| env::var(name).ok())
+}
+
+/// Get the colon-separated list of locales from environment variables looked
+/// up with `var`.
+fn language_list_from(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|name| var(name).filter(|v| !v.is_empty()))
+        .filter(|locale| !is_c_locale(locale))?;
+
+    Some(
+        var("LANGUAGE")
+            .filter(|list| !list.is_empty())
+            .unwrap_or(locale),
+    )
+}
+
+/// Get the languages from environment variables looked up with `var`, or
+/// `en-US` (followed by `en`) if there are none.
+fn langs_from_vars(var: impl Fn(&str) -> Option<String>) -> Vec<Language> {
+    match language_list_from(var).map(|list| langs_from_list(&list)) {
+        Some(langs) if !langs.is_empty() => langs,
+        _ => langs_from_list(DEFAULT_LANG),
+    }
+}
+
 #[inline(always)]
 pub(crate) fn lang() -> impl Iterator<Item = String> {
-    const DEFAULT_LANG: &str = "en_US";
-
-    let array = env::var("LANG")
-        .unwrap_or_default()
-        .split('.')
-        .next()
-        .unwrap_or(DEFAULT_LANG)
-        .to_string();
-    let array = if array == "C" {
-        DEFAULT_LANG.to_string()
+    let lang = env::var("LANG").unwrap_or_default();
+    let array = if lang.is_empty() || is_c_locale(&lang) {
+        DEFAULT_LANG
     } else {
-        array
+        lang.split('.').next().unwrap_or(DEFAULT_LANG)
     };
 
     LangIter {
@@ -216,166 +695,1212 @@ pub(crate) fn lang() -> impl Iterator<Item = String> {
     }
 }
 
-#[cfg(not(target_os = "macos"))]
-fn read_devicename_from_machine_info() -> Result<OsString> {
-    let machine_info = fs::read("/etc/machine-info")?;
+/// Parse the node name from the contents of `/etc/nodename`, skipping comments
+/// and removing surrounding quotes.
+#[cfg(target_os = "illumos")]
+fn nodename_from_file(data: &str) -> Option<&str> {
+    data.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .find(|line| !line.is_empty())
+        .map(|line| line.trim_matches('"').trim_matches('\'').trim())
+        .filter(|nodename| !nodename.is_empty())
+}
+
+/// Get `hostname`, or else `HOSTNAME` in `var` if the hostname is empty.
+///
+/// Minimal containers may not set a hostname, but set `HOSTNAME`.
+fn hostname_or_var(
+    hostname: String,
+    var: impl Fn(&str) -> Option<String>,
+) -> String {
+    if hostname.is_empty() {
+        if let Some(hostname) = var("HOSTNAME").filter(|name| !name.is_empty())
+        {
+            return hostname;
+        }
+    }
 
+    hostname
+}
+
+/// Parse `PRETTY_HOSTNAME` from the contents of `/etc/machine-info`, where
+/// systemd-hostnamed (`hostnamectl`) stores it.  Returns `None` if it's
+/// missing or empty.
+fn pretty_hostname_from_machine_info(machine_info: &[u8]) -> Option<OsString> {
     for i in machine_info.split(|b| *b == b'\n') {
-        let mut j = i.split(|b| *b == b'=');
+        let mut j = i.splitn(2, |b| *b == b'=');
 
         if j.next() == Some(b"PRETTY_HOSTNAME") {
-            if let Some(value) = j.next() {
+            if let Some(mut value) = j.next() {
+                if value.last() == Some(&b'\r') {
+                    value = &value[..value.len() - 1];
+                }
+
+                // Remove the quotes around the value
                 // FIXME: Can " be escaped in pretty name?
-                return Ok(OsString::from_vec(value.to_vec()));
+                for &quote in b"\"'" {
+                    if value.len() >= 2
+                        && value[0] == quote
+                        && value[value.len() - 1] == quote
+                    {
+                        value = &value[1..value.len() - 1];
+                    }
+                }
+
+                if !value.is_empty() {
+                    return Some(OsString::from_vec(value.to_vec()));
+                }
             }
         }
     }
 
-    Err(Error::new(ErrorKind::NotFound, "Missing record"))
+    None
 }
 
-impl Target for Os {
-    fn langs(self) -> Vec<Language> {
-        todo!()
-    }
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn read_devicename_from_machine_info() -> Result<OsString> {
+    let machine_info = fs::read("/etc/machine-info")?;
 
-    fn realname(self) -> Result<OsString> {
-        getpwuid(Name::Real)
-    }
+    pretty_hostname_from_machine_info(&machine_info)
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "Missing record"))
+}
 
-    fn username(self) -> Result<OsString> {
-        getpwuid(Name::User)
+/// Get the pretty hostname from `/etc/machine-info` if there is one, and
+/// otherwise the hostname.
+///
+/// This doesn't ask systemd-hostnamed over D-Bus: its `PrettyHostname`
+/// property is read from the same file, so it could only ever agree, and
+/// querying it would need a D-Bus client (and a running bus).
+fn pretty_hostname_or(
+    machine_info: Option<&[u8]>,
+    hostname: impl FnOnce() -> Result<OsString>,
+) -> Result<OsString> {
+    match machine_info.and_then(pretty_hostname_from_machine_info) {
+        Some(pretty_hostname) => Ok(pretty_hostname),
+        None => hostname(),
     }
+}
 
-    fn devicename(self) -> Result<OsString> {
-        #[cfg(target_os = "macos")]
-        {
-            // On macOS, the computer name is fetched from the System
-            // Configuration framework.
-            let out = os_from_cfstring(unsafe {
-                SCDynamicStoreCopyComputerName(null_mut(), null_mut())
-            });
+#[cfg(not(target_os = "macos"))]
+const OS_RELEASE: &str = "/etc/os-release";
 
-            if out.as_bytes().is_empty() {
-                return Err(Error::new(ErrorKind::InvalidData, "Empty record"));
-            }
+/// Read a release file (such as `/etc/os-release`), including the path in the
+/// error.
+#[cfg(not(target_os = "macos"))]
+fn read_release_file(path: &str) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|e| {
+        Error::new(e.kind(), format!("Failed to read {}: {}", path, e))
+    })
+}
 
-            Ok(out)
-        }
+#[cfg(not(target_os = "macos"))]
+fn distro_lsb_release(data: &str) -> Option<String> {
+    if let Some(description) = release_value(data, "DISTRIB_DESCRIPTION") {
+        return Some(description.to_string());
+    }
 
-        #[cfg(not(target_os = "macos"))]
-        {
-            // On other platforms, attempt to read /etc/machine-info, and fall
-            // back to the nodename.
-            let mi_error = match read_devicename_from_machine_info() {
-                Ok(devicename) => return Ok(devicename),
-                Err(mi_error) => mi_error,
-            };
+    let id = release_value(data, "DISTRIB_ID")?;
 
-            let uname_error = match uname() {
-                Ok(uts_name) => return Ok(uts_name.nodename().to_os_string()),
-                Err(uname_error) => uname_error,
-            };
+    Some(
+        if let Some(release) = release_value(data, "DISTRIB_RELEASE") {
+            join_name_version(id, release)
+        } else {
+            id.to_string()
+        },
+    )
+}
 
-            // Make a new error representing the fact that both methods failed.
-            Err(Error::new(
-                ErrorKind::Other,
-                format!(
-                    "failed to obtain device name: reading from \
-                     /etc/machine-info failed with \"{}\", \
-                     and uname() failed with \"{}\"",
-                    mi_error, uname_error
-                ),
-            ))
-        }
+/// `/etc/redhat-release` is a single line (example: "CentOS release 6.10
+/// (Final)").
+#[cfg(not(target_os = "macos"))]
+fn distro_redhat_release(data: &str) -> Option<String> {
+    data.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| line.to_string())
+}
+
+/// `/etc/debian_version` only contains the version (example: "12.5").
+#[cfg(not(target_os = "macos"))]
+fn distro_debian_version(data: &str) -> Option<String> {
+    let version = data.trim();
+
+    if version.is_empty() {
+        None
+    } else {
+        Some(format!("Debian {}", version))
     }
+}
 
-    fn hostname(self) -> Result<String> {
-        gethostname()?.into_string().map_err(|_| {
-            Error::new(ErrorKind::InvalidData, "Hostname not valid UTF-8")
-        })
+/// `/etc/alpine-release` only contains the version (example: "3.19.1"), so it's
+/// combined with the name from `/etc/os-release`.  Returns `None` if
+/// `/etc/os-release` already has a `VERSION_ID`, or isn't for Alpine.
+#[cfg(not(target_os = "macos"))]
+fn distro_alpine_release(
+    os_release: Option<&str>,
+    data: &str,
+) -> Option<String> {
+    let version = data.trim();
+
+    if version.is_empty() {
+        return None;
     }
 
-    fn distro(self) -> Result<String> {
-        #[cfg(target_os = "macos")]
-        {
-            if let Ok(data) = fs::read_to_string(
-                "/System/Library/CoreServices/ServerVersion.plist",
-            ) {
-                distro_xml(data)
-            } else if let Ok(data) = fs::read_to_string(
-                "/System/Library/CoreServices/SystemVersion.plist",
-            ) {
-                distro_xml(data)
-            } else {
-                Err(Error::new(ErrorKind::NotFound, "Missing record"))
+    let name = match os_release {
+        Some(os_release) => {
+            if release_value(os_release, "VERSION_ID").is_some()
+                || release_value(os_release, "ID") != Some("alpine")
+            {
+                return None;
             }
+
+            release_value(os_release, "NAME").unwrap_or("Alpine Linux")
         }
+        None => "Alpine Linux",
+    };
 
-        #[cfg(not(target_os = "macos"))]
+    Some(join_name_version(name, version))
+}
+
+/// Gentoo is a rolling release, so its `/etc/os-release` has no version; the
+/// profile (the target of the `/etc/portage/make.profile` symlink, example:
+/// `default/linux/amd64/23.0/desktop`) is added, or else the baselayout
+/// release from `/etc/gentoo-release` (example: "Gentoo Base System release
+/// 2.14").  Returns `None` if `/etc/os-release` has a version, or isn't for
+/// Gentoo.
+#[cfg(not(target_os = "macos"))]
+fn distro_gentoo(
+    os_release: &str,
+    profile: Option<&str>,
+    gentoo_release: Option<&str>,
+) -> Option<String> {
+    if release_value(os_release, "ID") != Some("gentoo")
+        || release_value(os_release, "VERSION_ID").is_some()
+    {
+        return None;
+    }
+
+    let name = release_value(os_release, "PRETTY_NAME")
+        .or_else(|| release_value(os_release, "NAME"))
+        .unwrap_or("Gentoo Linux");
+
+    if let Some(profile) = profile
+        .and_then(|link| link.rfind("/profiles/").map(|i| &link[i + 10..]))
+        .map(|profile| profile.trim_end_matches('/'))
+        .filter(|profile| !profile.is_empty())
+    {
+        return Some(format!("{}, profile {}", name, profile));
+    }
+
+    let release = gentoo_release?.trim();
+    let version = &release[release.rfind("release ")? + 8..];
+
+    Some(join_name_version(name, version.trim()))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn distro_chromeos(data: &str) -> Option<String> {
+    let version = release_value(data, "GOOGLE_RELEASE")
+        .or_else(|| release_value(data, "CHROMEOS_RELEASE_DESCRIPTION"))?;
+
+    Some(format!("Chrome OS {}", version))
+}
+
+/// Parse the contents of a DMI (SMBIOS) field file from sysfs.  Returns `None`
+/// if it's empty, or one of the placeholders firmware leaves unchanged.
+#[cfg(target_os = "linux")]
+fn dmi_value(data: &str) -> Option<&str> {
+    const PLACEHOLDERS: &[&str] = &[
+        "Default string",
+        "None",
+        "Not Applicable",
+        "Not Specified",
+        "O.E.M.",
+        "OEM",
+        "System manufacturer",
+        "System Product Name",
+        "To Be Filled By O.E.M.",
+    ];
+
+    Some(data.trim()).filter(|value| {
+        !value.is_empty()
+            && !PLACEHOLDERS
+                .iter()
+                .any(|placeholder| placeholder.eq_ignore_ascii_case(value))
+    })
+}
+
+/// Read a DMI (SMBIOS) field exposed through sysfs.
+///
+/// Some of these files are only readable by root, so permission errors are
+/// reported as [`ErrorKind::NotFound`].
+#[cfg(target_os = "linux")]
+fn dmi_field(name: &str) -> Result<String> {
+    let path = format!("/sys/devices/virtual/dmi/id/{}", name);
+    let data = fs::read_to_string(path).map_err(|e| {
+        if e.kind() == ErrorKind::PermissionDenied {
+            Error::new(ErrorKind::NotFound, e)
+        } else {
+            e
+        }
+    })?;
+
+    dmi_value(&data).map(ToString::to_string).ok_or_else(|| {
+        Error::new(ErrorKind::NotFound, "Empty or placeholder DMI field")
+    })
+}
+
+/// Find the filesystem type of the innermost mount containing `path` in the
+/// contents of `/proc/self/mountinfo`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn mountinfo_fs_type(data: &str, path: &Path) -> Option<String> {
+    let mut found: Option<(usize, &str)> = None;
+
+    for line in data.lines() {
+        // Fields: ID, parent ID, major:minor, root, mount point, options,
+        // optional fields…, "-", filesystem type, source, super options
+        let mut fields = line.split(' ');
+        let (mount_point, fs_type) =
+            match (fields.nth(4), fields.skip_while(|f| *f != "-").nth(1)) {
+                (Some(mount_point), Some(fs_type)) => (mount_point, fs_type),
+                _ => continue,
+            };
+        let mount_point = mountinfo_unescape(mount_point);
+
+        // Later mounts on the same mount point hide earlier ones
+        if path.starts_with(&mount_point)
+            && found.map_or(true, |(len, _)| mount_point.len() >= len)
+        {
+            found = Some((mount_point.len(), fs_type));
+        }
+    }
+
+    found.map(|(_, fs_type)| fs_type.to_string())
+}
+
+/// Get the number of CPUs allowed by a cgroup v2 `cpu.max` file, which
+/// contains the quota and period in microseconds (example: "150000 100000" for
+/// 1.5 CPUs, or "max 100000" when unlimited).
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn cgroup_v2_cpus(cpu_max: &str) -> Option<usize> {
+    let mut fields = cpu_max.split_whitespace();
+    let quota = fields.next()?.parse().ok()?;
+    let period = fields.next().map_or(Some(100_000), |p| p.parse().ok())?;
+
+    cgroup_cpus(quota, period)
+}
+
+/// Get the number of CPUs allowed by cgroup v1's `cpu.cfs_quota_us` and
+/// `cpu.cfs_period_us` files, where a quota of -1 is unlimited.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn cgroup_v1_cpus(quota: &str, period: &str) -> Option<usize> {
+    cgroup_cpus(quota.trim().parse().ok()?, period.trim().parse().ok()?)
+}
+
+/// Divide a cgroup CPU quota by its period, rounding down to at least 1.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn cgroup_cpus(quota: i64, period: i64) -> Option<usize> {
+    if quota <= 0 || period <= 0 {
+        return None;
+    }
+
+    Some(usize::try_from(quota / period).ok()?.max(1))
+}
+
+/// Find the program's cgroup in the contents of `/proc/self/cgroup`: the
+/// cgroup v2 one if `controller` is `None`, otherwise the cgroup v1 one with
+/// that controller (example: `cpu`).
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn proc_cgroup_path<'a>(
+    data: &'a str,
+    controller: Option<&str>,
+) -> Option<&'a str> {
+    data.lines().find_map(|line| {
+        let mut fields = line.splitn(3, ':');
+        let id = fields.next()?;
+        let controllers = fields.next()?;
+        let path = fields.next()?;
+        let found = match controller {
+            Some(controller) => controllers.split(',').any(|c| c == controller),
+            None => id == "0" && controllers.is_empty(),
+        };
+
+        Some(path).filter(|_| found)
+    })
+}
+
+/// Get the smallest CPU limit of the cgroup at `path` and of its ancestors,
+/// which also apply to it.  `limit` gets the limit of the cgroup at a path
+/// relative to the controller's mount point.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn cgroup_min_limit(
+    path: &str,
+    limit: impl Fn(&Path) -> Option<usize>,
+) -> Option<usize> {
+    Path::new(path)
+        .ancestors()
+        .filter_map(|cgroup| limit(cgroup.strip_prefix("/").unwrap_or(cgroup)))
+        .min()
+}
+
+/// Get the CPU limit of the program's cgroup (from `/proc/self/cgroup`) and
+/// its ancestors.
+///
+/// In a container, the cgroup's path may be one from outside it, so the
+/// limits of the ancestors that are visible (including the root of the
+/// mount, which is the container's cgroup) are used.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn cgroup_cpu_limit() -> Option<usize> {
+    let proc_cgroup =
+        fs::read_to_string("/proc/self/cgroup").unwrap_or_default();
+
+    if Path::new("/sys/fs/cgroup/cgroup.controllers").exists() {
+        let path = proc_cgroup_path(&proc_cgroup, None).unwrap_or("/");
+
+        return cgroup_min_limit(path, |cgroup| {
+            let cpu_max =
+                Path::new("/sys/fs/cgroup").join(cgroup).join("cpu.max");
+
+            cgroup_v2_cpus(&fs::read_to_string(cpu_max).ok()?)
+        });
+    }
+
+    let path = proc_cgroup_path(&proc_cgroup, Some("cpu")).unwrap_or("/");
+
+    ["/sys/fs/cgroup/cpu", "/sys/fs/cgroup/cpu,cpuacct"]
+        .iter()
+        .map(Path::new)
+        .find(|mount| mount.exists())
+        .and_then(|mount| {
+            cgroup_min_limit(path, |cgroup| {
+                let dir = mount.join(cgroup);
+                let quota =
+                    fs::read_to_string(dir.join("cpu.cfs_quota_us")).ok()?;
+                let period =
+                    fs::read_to_string(dir.join("cpu.cfs_period_us")).ok()?;
+
+                cgroup_v1_cpus(&quota, &period)
+            })
+        })
+}
+
+/// Get the number of CPUs in the program's affinity mask.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn affinity_cpus() -> Option<usize> {
+    let set = sched_getaffinity(Pid::from_raw(0)).ok()?;
+    let cpus = (0..CpuSet::count())
+        .filter(|cpu| set.is_set(*cpu).unwrap_or(false))
+        .count();
+
+    Some(cpus).filter(|cpus| *cpus > 0)
+}
+
+/// Decode the octal escapes (example: `\040` for a space) in a mount point.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn mountinfo_unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escape = bytes.get(i + 1..i + 4).filter(|digits| {
+            bytes[i] == b'\\'
+                && digits.iter().all(|d| (b'0'..=b'7').contains(d))
+        });
+
+        if let Some(digits) = escape {
+            out.push(
+                digits
+                    .iter()
+                    .fold(0u8, |n, d| n.wrapping_mul(8) + (d - b'0')),
+            );
+            i += 4;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Find the fully qualified name of `hostname` in the contents of
+/// `/etc/hosts`: the first name with a domain whose first label is `hostname`.
+fn hosts_fqdn(data: &str, hostname: &str) -> Option<String> {
+    data.lines()
+        .flat_map(|line| {
+            let line = line.split('#').next().unwrap_or_default();
+
+            // Skip the address
+            line.split_whitespace().skip(1)
+        })
+        .map(|name| name.trim_end_matches('.'))
+        .find(|name| {
+            name.find('.').map_or(false, |dot| {
+                name[..dot].eq_ignore_ascii_case(hostname)
+                    && dot + 1 < name.len()
+            })
+        })
+        .map(str::to_string)
+}
+
+/// Find the libc from the names of the dynamic linkers that are installed.
+#[cfg(target_os = "linux")]
+fn linux_libc() -> Libc {
+    let mut names = Vec::new();
+
+    for dir in &["/lib", "/lib64", "/usr/lib"] {
+        if let Ok(entries) = fs::read_dir(dir) {
+            names.extend(entries.flatten().map(|entry| entry.file_name()));
+        }
+    }
+
+    libc_from_linkers(names.iter().map(|name| name.to_string_lossy()))
+}
+
+/// Find the libc from the file names of dynamic linkers (example:
+/// `ld-musl-x86_64.so.1` or `ld-linux-x86-64.so.2`).
+///
+/// Musl is preferred, since systems with musl may also have a glibc
+/// compatibility layer (example: gcompat on Alpine).
+#[cfg(any(target_os = "linux", test))]
+fn libc_from_linkers<S: AsRef<str>>(names: impl Iterator<Item = S>) -> Libc {
+    let mut libc = Libc::Unknown;
+
+    for name in names {
+        let name = name.as_ref();
+
+        if name.starts_with("ld-musl-") {
+            return Libc::Musl;
+        }
+
+        if name.starts_with("ld-linux") {
+            libc = Libc::Glibc;
+        }
+    }
+
+    libc
+}
+
+/// Convert the machine hardware name from `uname -m` into an [`Arch`].
+///
+/// Aliases registered with [`crate::register_arch_alias()`] take precedence.
+fn arch_from_machine(machine: &str) -> Arch {
+    if let Some(arch) = crate::arch_alias(machine) {
+        return arch;
+    }
+
+    match machine.to_ascii_lowercase().as_str() {
+        "aarch64" | "arm64" | "aarch64_be" | "armv8b" | "armv8l" => Arch::Arm64,
+        "arm64ec" => Arch::Arm64Ec,
+        "armv5" => Arch::ArmV5,
+        "armv6" | "arm" => Arch::ArmV6,
+        "armv7" => Arch::ArmV7,
+        "i386" => Arch::I386,
+        "i586" => Arch::I586,
+        "i686" | "i686-at386" | "x86" | "x86pc" => Arch::I686,
+        // illumos has only had a 64-bit kernel on x86 since 2018
+        "i86pc" => Arch::X64,
+        "mips" => Arch::Mips,
+        "mipsel" => Arch::MipsEl,
+        "mips64" => Arch::Mips64,
+        "mips64el" => Arch::Mips64El,
+        "powerpc" | "ppc" => Arch::PowerPc,
+        "powerpcle" | "ppcle" => Arch::PowerPcLe,
+        "powerpc64" | "ppc64" => Arch::PowerPc64,
+        "powerpc64le" | "ppc64le" => Arch::PowerPc64Le,
+        "riscv32" => Arch::Riscv32,
+        "riscv64" => Arch::Riscv64,
+        "riscv128" => Arch::Riscv128,
+        "s390x" => Arch::S390x,
+        "sparc" => Arch::Sparc,
+        // Solaris and illumos report the platform group rather than the ISA
+        "sparc64" | "sparcv9" | "sun4u" | "sun4v" => Arch::Sparc64,
+        "x86_64" | "amd64" => Arch::X64,
+        "e2k" => Arch::E2k,
+        "m68k" => Arch::M68k,
+        "sh4" | "sh" => Arch::Sh4,
+        _ => Arch::Unknown(machine.to_string()),
+    }
+}
+
+/// Parse a `DESKTOP_SESSION` or `XDG_CURRENT_DESKTOP` value.
+///
+/// `XDG_CURRENT_DESKTOP` may be a colon-separated list, ordered from most to
+/// least specific (example: `ubuntu:GNOME`).
+fn desktop_env_from_session(session: &str) -> DesktopEnv {
+    let mut envs = session.split(':').filter_map(desktop_env_from_token);
+
+    match envs.next() {
+        // Ubuntu sessions list the underlying desktop environment second, and
+        // only Ubuntu-branded GNOME is reported as `Ubuntu`.
+        Some(DesktopEnv::Ubuntu) => match envs.next() {
+            None | Some(DesktopEnv::Gnome) => DesktopEnv::Ubuntu,
+            Some(env) => env,
+        },
+        Some(env) => env,
+        None => DesktopEnv::Unknown(session.to_string()),
+    }
+}
+
+/// Get the desktop environment from the session variables in `var`.
+#[cfg(not(target_os = "macos"))]
+fn desktop_env_from_vars(var: impl Fn(&str) -> Option<String>) -> DesktopEnv {
+    let mut unknown = None;
+
+    // Later variables may be recognized when earlier ones aren't
+    for name in &[
+        "DESKTOP_SESSION",
+        "XDG_CURRENT_DESKTOP",
+        "GDMSESSION",
+        "XDG_SESSION_DESKTOP",
+    ] {
+        let session = match var(name) {
+            Some(session) => session,
+            None => continue,
+        };
+
+        match desktop_env_from_session(&session) {
+            DesktopEnv::Unknown(session) => {
+                unknown.get_or_insert(session);
+            }
+            env => return env,
+        }
+    }
+
+    DesktopEnv::Unknown(unknown.unwrap_or_else(|| "Unknown".to_string()))
+}
+
+/// Standalone window managers that set the session variables themselves, by
+/// their canonical names
+const WINDOW_MANAGERS: &[&str] = &[
+    "awesome",
+    "bspwm",
+    "dwm",
+    "herbstluftwm",
+    "Hyprland",
+    "leftwm",
+    "Qtile",
+    "river",
+    "spectrwm",
+    "sway",
+    "xmonad",
+];
+
+fn desktop_env_from_token(token: &str) -> Option<DesktopEnv> {
+    Some(if token.eq_ignore_ascii_case("AQUA") {
+        DesktopEnv::Aqua
+    } else if token.eq_ignore_ascii_case("GNOME") {
+        DesktopEnv::Gnome
+    } else if token.eq_ignore_ascii_case("LXDE") {
+        DesktopEnv::Lxde
+    } else if token.eq_ignore_ascii_case("OPENBOX") {
+        DesktopEnv::Openbox
+    } else if token.eq_ignore_ascii_case("I3") {
+        DesktopEnv::I3
+    } else if token.eq_ignore_ascii_case("UBUNTU") {
+        DesktopEnv::Ubuntu
+    } else if token.eq_ignore_ascii_case("UNITY") {
+        DesktopEnv::Unity
+    } else if token.eq_ignore_ascii_case("PANTHEON") {
+        DesktopEnv::Pantheon
+    } else if token.eq_ignore_ascii_case("DEEPIN")
+        || token.eq_ignore_ascii_case("DDE")
+    {
+        DesktopEnv::Deepin
+    } else if token.eq_ignore_ascii_case("COSMIC") {
+        DesktopEnv::Cosmic
+    } else if token.eq_ignore_ascii_case("PLASMA5") {
+        DesktopEnv::Kde
+    } else if let Some(wm) = WINDOW_MANAGERS
+        .iter()
+        .find(|wm| token.eq_ignore_ascii_case(wm))
+    {
+        DesktopEnv::WindowManager(wm.to_string())
+    // TODO: Other Linux Desktop Environments
+    } else {
+        return None;
+    })
+}
+
+/// Read `_NET_WM_NAME` from the window manager's supporting window.
+#[cfg(all(feature = "x11", not(target_os = "macos")))]
+fn x11_window_manager() -> Option<String> {
+    use x11rb::{
+        connection::Connection,
+        protocol::xproto::{AtomEnum, ConnectionExt},
+    };
+
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen)?.root;
+    let atom = |name: &[u8]| {
+        conn.intern_atom(true, name)
+            .ok()?
+            .reply()
+            .ok()
+            .map(|reply| reply.atom)
+            .filter(|atom| *atom != u32::from(AtomEnum::NONE))
+    };
+    let wm_check = atom(b"_NET_SUPPORTING_WM_CHECK")?;
+    let wm_name = atom(b"_NET_WM_NAME")?;
+    let utf8_string = atom(b"UTF8_STRING")?;
+    let window = conn
+        .get_property(false, root, wm_check, AtomEnum::WINDOW, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?
+        .value32()?
+        .next()?;
+    let name = conn
+        .get_property(false, window, wm_name, utf8_string, 0, std::u32::MAX)
+        .ok()?
+        .reply()
+        .ok()?
+        .value;
+
+    String::from_utf8(name).ok().filter(|name| !name.is_empty())
+}
+
+/// Get the git commit Hyprland was built from, the first field of
+/// `HYPRLAND_INSTANCE_SIGNATURE` (`<commit>_<timestamp>_<random>`).
+#[cfg(not(target_os = "macos"))]
+fn hyprland_commit(signature: &str) -> Option<&str> {
+    let commit = &signature[..signature.find('_')?];
+
+    Some(commit).filter(|commit| {
+        commit.len() == 40 && commit.bytes().all(|b| b.is_ascii_hexdigit())
+    })
+}
+
+/// Run a compositor's IPC client, and get the string value of `key` from its
+/// JSON output.
+#[cfg(all(feature = "compositor-ipc", not(target_os = "macos")))]
+fn compositor_ipc(program: &str, args: &[&str], key: &str) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    json_str_field(&String::from_utf8_lossy(&output.stdout), key)
+}
+
+/// Get the string value of `key` in a JSON object, without escape sequences.
+///
+/// Only the object's own fields are searched, not those of nested objects.
+#[cfg(all(feature = "compositor-ipc", not(target_os = "macos")))]
+fn json_str_field(json: &str, key: &str) -> Option<String> {
+    // Get the contents of the string starting after the quote at `start`, and
+    // the index after its closing quote
+    fn string(json: &str, start: usize) -> Option<(&str, usize)> {
+        let bytes = json.as_bytes();
+        let mut i = start;
+
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => i += 2,
+                b'"' => return Some((&json[start..i], i + 1)),
+                _ => i += 1,
+            }
+        }
+
+        None
+    }
+
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while let Some(byte) = json.as_bytes().get(i) {
+        match byte {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth = depth.checked_sub(1)?,
+            b'"' => {
+                let (name, end) = string(json, i + 1)?;
+                let rest = json[end..].trim_start();
+
+                if depth == 1 && name == key && rest.starts_with(':') {
+                    let value = rest[1..].trim_start();
+
+                    if !value.starts_with('"') {
+                        return None;
+                    }
+
+                    return Some(string(value, 1)?.0)
+                        .filter(|value| {
+                            !value.is_empty() && !value.contains('\\')
+                        })
+                        .map(str::to_string);
+                }
+
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+impl Target for Os {
+    fn langs(self) -> Vec<Language> {
+        langs_from_vars(|name| env::var(name).ok())
+    }
+
+    fn primary_lang(self) -> Option<Language> {
+        language_list()
+            .and_then(|list| first_lang_from_list(&list))
+            .or_else(|| first_lang_from_list(DEFAULT_LANG))
+    }
+
+    fn realname(self) -> Result<OsString> {
+        getpwuid().map(|(_username, realname)| realname_fallback(realname))
+    }
+
+    fn username(self) -> Result<OsString> {
+        getpwuid().map(|(username, _realname)| username)
+    }
+
+    fn user(self) -> Result<(OsString, OsString)> {
+        getpwuid()
+    }
+
+    fn login(self) -> Result<OsString> {
+        getlogin()
+    }
+
+    #[inline(always)]
+    fn uid(self) -> Result<UserId> {
+        Ok(UserId::from(Uid::effective().as_raw()))
+    }
+
+    #[inline(always)]
+    fn gid(self) -> Result<GroupId> {
+        Ok(GroupId::from(Gid::effective().as_raw()))
+    }
+
+    fn devicename(self) -> Result<OsString> {
+        #[cfg(target_os = "macos")]
+        {
+            // On macOS, the computer name is fetched from the System
+            // Configuration framework.
+            let out = os_from_cfstring(unsafe {
+                SCDynamicStoreCopyComputerName(null_mut(), null_mut())
+            });
+
+            if out.as_bytes().is_empty() {
+                return Err(Error::new(ErrorKind::InvalidData, "Empty record"));
+            }
+
+            Ok(out)
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.pretty_hostname()
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+        {
+            // On other platforms, attempt to read /etc/machine-info, and fall
+            // back to /etc/nodename (on illumos), `kern.hostname` (on the
+            // BSDs) or the nodename from `uname()`.
+            let mi_error = match read_devicename_from_machine_info() {
+                Ok(devicename) => return Ok(devicename),
+                Err(mi_error) => mi_error,
+            };
+
+            #[cfg(target_os = "illumos")]
+            {
+                if let Some(nodename) = fs::read_to_string("/etc/nodename")
+                    .ok()
+                    .as_deref()
+                    .and_then(nodename_from_file)
+                {
+                    return Ok(nodename.into());
+                }
+            }
+
+            // The BSDs don't have a separate "pretty" name
+            #[cfg(any(
+                target_os = "freebsd",
+                target_os = "dragonfly",
+                target_os = "netbsd"
+            ))]
+            {
+                if let Ok(hostname) = sysctl_string("kern.hostname") {
+                    return Ok(hostname.into());
+                }
+            }
+
+            let uname_error = match uname() {
+                Ok(uts_name) => return Ok(uts_name.nodename().to_os_string()),
+                Err(uname_error) => uname_error,
+            };
+
+            // Make a new error representing the fact that both methods failed.
+            Err(Error::new(
+                ErrorKind::Other,
+                format!(
+                    "failed to obtain device name: reading from \
+                     /etc/machine-info failed with \"{}\", \
+                     and uname() failed with \"{}\"",
+                    mi_error, uname_error
+                ),
+            ))
+        }
+    }
+
+    fn pretty_hostname(self) -> Result<OsString> {
+        let machine_info = fs::read("/etc/machine-info").ok();
+
+        pretty_hostname_or(machine_info.as_deref(), || Ok(gethostname()?))
+    }
+
+    fn local_hostname(self) -> Result<OsString> {
+        #[cfg(target_os = "macos")]
+        {
+            let out = os_from_cfstring(unsafe {
+                SCDynamicStoreCopyLocalHostName(null_mut())
+            });
+
+            if out.as_bytes().is_empty() {
+                return Err(Error::new(ErrorKind::NotFound, "Empty record"));
+            }
+
+            Ok(out)
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            Err(unsupported())
+        }
+    }
+
+    fn hostname(self) -> Result<String> {
+        let hostname = gethostname()?.into_string().map_err(|_| {
+            Error::new(ErrorKind::InvalidData, "Hostname not valid UTF-8")
+        })?;
+
+        Ok(hostname_or_var(hostname, |name| env::var(name).ok()))
+    }
+
+    fn distro(self) -> Result<String> {
+        #[cfg(target_os = "macos")]
+        {
+            if let Ok(data) =
+                fs::read("/System/Library/CoreServices/ServerVersion.plist")
+            {
+                distro_plist(data)
+            } else if let Ok(data) =
+                fs::read("/System/Library/CoreServices/SystemVersion.plist")
+            {
+                distro_plist(data)
+            } else if let Ok(version) = sysctl_string("kern.osproductversion") {
+                // Readable even in the App Sandbox (macOS 10.13.4+)
+                Ok(format!("macOS {}", version))
+            } else {
+                Err(Error::new(
+                    ErrorKind::NotFound,
+                    "Missing /System/Library/CoreServices/SystemVersion.plist",
+                ))
+            }
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            let lsb_release = fs::read_to_string("/etc/lsb-release");
+
+            // Chrome OS only describes itself in `/etc/lsb-release`
+            if let Some(distro) =
+                lsb_release.as_ref().ok().and_then(|d| distro_chromeos(d))
+            {
+                return Ok(distro);
+            }
+
+            let os_release = read_release_file(OS_RELEASE)
+                .map(|data| String::from_utf8_lossy(&data).into_owned());
+
+            // Alpine may only have its version in `/etc/alpine-release`
+            if let Some(distro) = fs::read_to_string("/etc/alpine-release")
+                .ok()
+                .and_then(|data| {
+                    distro_alpine_release(
+                        os_release.as_ref().ok().map(String::as_str),
+                        &data,
+                    )
+                })
+            {
+                return Ok(distro);
+            }
+
+            // Gentoo has no version in `/etc/os-release`
+            if let Some(distro) = os_release.as_ref().ok().and_then(|data| {
+                if release_value(data, "ID") != Some("gentoo") {
+                    return None;
+                }
+
+                let profile = fs::read_link("/etc/portage/make.profile").ok();
+                let release = fs::read_to_string("/etc/gentoo-release").ok();
+
+                distro_gentoo(
+                    data,
+                    profile.as_ref().and_then(|path| path.to_str()),
+                    release.as_deref(),
+                )
+            }) {
+                return Ok(distro);
+            }
+
+            let os_release = os_release.map(|data| distro_os_release(&data));
+
+            if let Ok(Some(distro)) = os_release {
+                return Ok(distro);
+            }
+
+            if let Some(distro) = lsb_release
+                .as_ref()
+                .ok()
+                .and_then(|d| distro_lsb_release(d))
+            {
+                return Ok(distro);
+            }
+
+            // Legacy and minimal systems without os-release
+            if let Some(distro) = fs::read_to_string("/etc/redhat-release")
+                .ok()
+                .and_then(|data| distro_redhat_release(&data))
+            {
+                return Ok(distro);
+            }
+
+            if let Some(distro) = fs::read_to_string("/etc/debian_version")
+                .ok()
+                .and_then(|data| distro_debian_version(&data))
+            {
+                return Ok(distro);
+            }
+
+            Err(os_release.err().unwrap_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Failed to parse {}", OS_RELEASE),
+                )
+            }))
+        }
+    }
+
+    fn distro_info(self) -> Result<DistroInfo> {
+        #[cfg(target_os = "macos")]
+        {
+            Err(unsupported())
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            let data = read_release_file(OS_RELEASE)?;
+
+            Ok(distro_info_os_release(&String::from_utf8_lossy(&data)))
+        }
+    }
+
+    #[inline(always)]
+    fn kernel_version(self) -> Result<String> {
+        Ok(uname()?.release().to_string_lossy().into_owned())
+    }
+
+    fn uname_info(self) -> Result<UnameInfo> {
+        let uts_name = uname()?;
+        let field = |field: &OsStr| field.to_string_lossy().into_owned();
+
+        // Linux reports "(none)" when the domain name isn't set
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let domainname = Some(field(uts_name.domainname()))
+            .filter(|name| !name.is_empty() && name != "(none)");
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        let domainname = None;
+
+        Ok(UnameInfo {
+            sysname: field(uts_name.sysname()),
+            nodename: field(uts_name.nodename()),
+            release: field(uts_name.release()),
+            version: field(uts_name.version()),
+            machine: field(uts_name.machine()),
+            domainname,
+        })
+    }
+
+    fn model_identifier(self) -> Result<String> {
+        #[cfg(target_os = "macos")]
+        {
+            sysctl_string("hw.model")
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            Err(unsupported())
+        }
+    }
+
+    fn device_model(self) -> Result<String> {
+        #[cfg(target_os = "linux")]
+        {
+            dmi_field("product_name")
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(unsupported())
+        }
+    }
+
+    fn device_vendor(self) -> Result<String> {
+        #[cfg(target_os = "linux")]
+        {
+            dmi_field("sys_vendor")
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(unsupported())
+        }
+    }
+
+    fn cwd_fs_type(self) -> Result<String> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let cwd = env::current_dir()?;
+            let data = fs::read_to_string("/proc/self/mountinfo")?;
+
+            mountinfo_fs_type(&data, &cwd).ok_or_else(|| {
+                Error::new(ErrorKind::NotFound, "No mount for directory")
+            })
+        }
+
+        #[cfg(any(
+            target_os = "macos",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "openbsd"
+        ))]
+        {
+            Ok(statfs(".")?.filesystem_type_name().to_string())
+        }
+
+        #[cfg(not(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "openbsd"
+        )))]
+        {
+            Err(unsupported())
+        }
+    }
+
+    fn fqdn_local(self, hostname: &str) -> Result<String> {
+        let data = fs::read_to_string("/etc/hosts")?;
+
+        hosts_fqdn(&data, hostname).ok_or_else(|| {
+            Error::new(ErrorKind::NotFound, "Hostname not in /etc/hosts")
+        })
+    }
+
+    fn desktop_env(self) -> DesktopEnv {
+        #[cfg(target_os = "macos")]
+        {
+            // Remote logins and daemons aren't in a window server session
+            let session = unsafe { CGSessionCopyCurrentDictionary() };
+
+            if session.is_null() {
+                return DesktopEnv::Unknown("headless".to_string());
+            }
+
+            unsafe { CFRelease(session) };
+            desktop_env_from_session("Aqua")
+        }
+
+        #[cfg(not(target_os = "macos"))]
         {
-            let program = fs::read("/etc/os-release")?;
-            let distro = String::from_utf8_lossy(&program);
-            let err = || Error::new(ErrorKind::InvalidData, "Parsing failed");
-            let mut fallback = None;
-
-            for i in distro.split('\n') {
-                let mut j = i.split('=');
-
-                match j.next().ok_or_else(err)? {
-                    "PRETTY_NAME" => {
-                        return Ok(j
-                            .next()
-                            .ok_or_else(err)?
-                            .trim_matches('"')
-                            .to_string());
-                    }
-                    "NAME" => {
-                        fallback = Some(
-                            j.next()
-                                .ok_or_else(err)?
-                                .trim_matches('"')
-                                .to_string(),
-                        )
-                    }
-                    _ => {}
+            desktop_env_from_vars(|name| {
+                env::var_os(name)
+                    .map(|value| value.to_string_lossy().into_owned())
+            })
+        }
+    }
+
+    fn window_manager(self) -> Option<String> {
+        #[cfg(target_os = "macos")]
+        {
+            None
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            if env::var_os("WAYLAND_DISPLAY").is_some() {
+                // The compositor is the least specific desktop name.
+                let desktop = env::var("XDG_CURRENT_DESKTOP").ok()?;
+
+                return desktop
+                    .rsplit(':')
+                    .next()
+                    .filter(|compositor| !compositor.is_empty())
+                    .map(|compositor| compositor.to_string());
+            }
+
+            #[cfg(feature = "x11")]
+            {
+                if env::var_os("DISPLAY").is_some() {
+                    return x11_window_manager();
                 }
             }
 
-            fallback.ok_or_else(err)
+            None
         }
     }
 
-    fn desktop_env(self) -> DesktopEnv {
+    fn desktop_env_version(self) -> Option<String> {
         #[cfg(target_os = "macos")]
-        let env = "Aqua";
-        // FIXME: WhoAmI 2.0: use `let else`
-        #[cfg(not(target_os = "macos"))]
-        let env = env::var_os("DESKTOP_SESSION");
+        {
+            None
+        }
+
         #[cfg(not(target_os = "macos"))]
-        let env = if let Some(ref env) = env {
-            env.to_string_lossy()
-        } else {
-            return DesktopEnv::Unknown("Unknown".to_string());
-        };
+        {
+            if let Some(signature) = env::var_os("HYPRLAND_INSTANCE_SIGNATURE")
+            {
+                #[cfg(feature = "compositor-ipc")]
+                {
+                    let version =
+                        compositor_ipc("hyprctl", &["version", "-j"], "tag");
 
-        if env.eq_ignore_ascii_case("AQUA") {
-            DesktopEnv::Aqua
-        } else if env.eq_ignore_ascii_case("GNOME") {
-            DesktopEnv::Gnome
-        } else if env.eq_ignore_ascii_case("LXDE") {
-            DesktopEnv::Lxde
-        } else if env.eq_ignore_ascii_case("OPENBOX") {
-            DesktopEnv::Openbox
-        } else if env.eq_ignore_ascii_case("I3") {
-            DesktopEnv::I3
-        } else if env.eq_ignore_ascii_case("UBUNTU") {
-            DesktopEnv::Ubuntu
-        } else if env.eq_ignore_ascii_case("PLASMA5") {
-            DesktopEnv::Kde
-        // TODO: Other Linux Desktop Environments
-        } else {
-            DesktopEnv::Unknown(env.to_string())
+                    if version.is_some() {
+                        return version;
+                    }
+                }
+
+                return hyprland_commit(signature.to_str()?).map(String::from);
+            }
+
+            #[cfg(feature = "compositor-ipc")]
+            {
+                if env::var_os("SWAYSOCK").is_some() {
+                    return compositor_ipc(
+                        "swaymsg",
+                        &["-t", "get_version", "-r"],
+                        "human_readable",
+                    );
+                }
+            }
+
+            // Only the major version (example: "6")
+            env::var("KDE_SESSION_VERSION")
+                .ok()
+                .filter(|version| !version.is_empty())
         }
     }
 
@@ -383,6 +1908,9 @@ impl Target for Os {
     fn platform(self) -> Platform {
         #[cfg(not(any(
             target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos",
             target_os = "freebsd",
             target_os = "dragonfly",
             target_os = "bitrig",
@@ -399,6 +1927,22 @@ impl Target for Os {
             Platform::MacOS
         }
 
+        // Also iPadOS, which Rust doesn't tell apart from iOS
+        #[cfg(target_os = "ios")]
+        {
+            Platform::Ios
+        }
+
+        #[cfg(target_os = "tvos")]
+        {
+            Platform::TvOs
+        }
+
+        #[cfg(target_os = "watchos")]
+        {
+            Platform::WatchOs
+        }
+
         #[cfg(any(
             target_os = "freebsd",
             target_os = "dragonfly",
@@ -416,35 +1960,1109 @@ impl Target for Os {
         }
     }
 
+    #[inline(always)]
+    fn platform_detected(self) -> bool {
+        // Other unix-like targets are assumed to be Linux
+        cfg!(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "watchos",
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "bitrig",
+            target_os = "openbsd",
+            target_os = "netbsd",
+            target_os = "illumos"
+        ))
+    }
+
     #[inline(always)]
     fn arch(self) -> Result<Arch> {
+        // On macOS, skip the `uname` syscall and parsing the other fields
+        #[cfg(target_os = "macos")]
+        {
+            if let Ok(machine) = sysctl_string("hw.machine") {
+                return Ok(arch_from_machine(&machine));
+            }
+        }
+
         let uts_name = uname()?;
-        let arch_str = uts_name.machine().to_string_lossy();
 
-        Ok(match arch_str.as_ref() {
-            "aarch64" | "arm64" | "aarch64_be" | "armv8b" | "armv8l" => {
-                Arch::Arm64
+        Ok(arch_from_machine(&uts_name.machine().to_string_lossy()))
+    }
+
+    #[inline(always)]
+    fn native_arch(self) -> Result<Arch> {
+        #[cfg(target_os = "macos")]
+        {
+            let translated = sysctl_int("sysctl.proc_translated").ok();
+
+            self.arch().map(|arch| rosetta_arch(arch, translated))
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            self.arch()
+        }
+    }
+
+    #[inline(always)]
+    fn isatty(self, fd: c_int) -> bool {
+        isatty(fd).unwrap_or(false)
+    }
+
+    fn output_kind(self, fd: c_int) -> OutputKind {
+        if self.isatty(fd) {
+            return OutputKind::Terminal;
+        }
+
+        let stat = match fstat(fd) {
+            Ok(stat) => stat,
+            Err(_) => return OutputKind::Redirected,
+        };
+
+        match SFlag::from_bits_truncate(stat.st_mode) & SFlag::S_IFMT {
+            SFlag::S_IFIFO => OutputKind::Pipe,
+            SFlag::S_IFREG => OutputKind::File,
+            _ => OutputKind::Redirected,
+        }
+    }
+
+    fn libc(self) -> Libc {
+        if cfg!(target_os = "android") {
+            return Libc::Bionic;
+        }
+
+        // Programs can only run with the libc they target
+        if cfg!(all(target_os = "linux", target_env = "gnu")) {
+            return Libc::Glibc;
+        }
+
+        if cfg!(all(target_os = "linux", target_env = "musl")) {
+            return Libc::Musl;
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            linux_libc()
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            Libc::Unknown
+        }
+    }
+
+    fn available_parallelism(self) -> Option<usize> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let cpus = affinity_cpus().or_else(|| self.cpu_count());
+
+            match (cpus, cgroup_cpu_limit()) {
+                (Some(cpus), Some(limit)) => Some(cpus.min(limit)),
+                (cpus, limit) => cpus.or(limit),
             }
-            "armv5" => Arch::ArmV5,
-            "armv6" | "arm" => Arch::ArmV6,
-            "armv7" => Arch::ArmV7,
-            "i386" => Arch::I386,
-            "i586" => Arch::I586,
-            "i686" | "i686-AT386" => Arch::I686,
-            "mips" => Arch::Mips,
-            "mipsel" => Arch::MipsEl,
-            "mips64" => Arch::Mips64,
-            "mips64el" => Arch::Mips64El,
-            "powerpc" | "ppc" | "ppcle" => Arch::PowerPc,
-            "powerpc64" | "ppc64" | "ppc64le" => Arch::PowerPc64,
-            "powerpc64le" => Arch::PowerPc64Le,
-            "riscv32" => Arch::Riscv32,
-            "riscv64" => Arch::Riscv64,
-            "s390x" => Arch::S390x,
-            "sparc" => Arch::Sparc,
-            "sparc64" => Arch::Sparc64,
-            "x86_64" | "amd64" => Arch::X64,
-            _ => Arch::Unknown(arch_str.into_owned()),
-        })
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            self.cpu_count()
+        }
+    }
+
+    fn cpu_count(self) -> Option<usize> {
+        let cpus =
+            unsafe { nix::libc::sysconf(nix::libc::_SC_NPROCESSORS_ONLN) };
+
+        usize::try_from(cpus).ok().filter(|cpus| *cpus > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn desktop_env_unity() {
+        assert_eq!(desktop_env_from_session("Unity"), DesktopEnv::Unity);
+        assert_eq!(desktop_env_from_session("unity"), DesktopEnv::Unity);
+        assert_eq!(desktop_env_from_session("ubuntu:unity"), DesktopEnv::Unity);
+        assert_eq!(desktop_env_from_session("Unity:Unity7"), DesktopEnv::Unity);
+    }
+
+    #[test]
+    fn desktop_env_pantheon_deepin() {
+        for (session, expected) in &[
+            ("Pantheon", DesktopEnv::Pantheon),
+            ("pantheon", DesktopEnv::Pantheon),
+            ("PANTHEON", DesktopEnv::Pantheon),
+            ("Deepin", DesktopEnv::Deepin),
+            ("deepin", DesktopEnv::Deepin),
+            ("DDE", DesktopEnv::Deepin),
+            ("dde", DesktopEnv::Deepin),
+        ] {
+            assert_eq!(
+                &desktop_env_from_session(session),
+                expected,
+                "{}",
+                session
+            );
+        }
+
+        assert_eq!(DesktopEnv::Pantheon.to_string(), "Pantheon");
+        assert_eq!(DesktopEnv::Deepin.to_string(), "Deepin");
+    }
+
+    #[test]
+    fn desktop_env_cosmic() {
+        assert_eq!(desktop_env_from_session("COSMIC"), DesktopEnv::Cosmic);
+        assert_eq!(desktop_env_from_session("cosmic"), DesktopEnv::Cosmic);
+        assert_eq!(DesktopEnv::Cosmic.to_string(), "COSMIC");
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn desktop_env_vars() {
+        let unknown = |session: &str| DesktopEnv::Unknown(session.to_string());
+
+        for (vars, expected) in &[
+            (&[][..], unknown("Unknown")),
+            (&[("DESKTOP_SESSION", "gnome")][..], DesktopEnv::Gnome),
+            (
+                &[
+                    ("DESKTOP_SESSION", "plasma"),
+                    ("XDG_CURRENT_DESKTOP", "LXDE"),
+                ][..],
+                DesktopEnv::Lxde,
+            ),
+            (
+                &[
+                    ("DESKTOP_SESSION", "gnome"),
+                    ("XDG_CURRENT_DESKTOP", "LXDE"),
+                ][..],
+                DesktopEnv::Gnome,
+            ),
+            (&[("GDMSESSION", "pantheon")][..], DesktopEnv::Pantheon),
+            (
+                &[("XDG_SESSION_DESKTOP", "sway")][..],
+                DesktopEnv::WindowManager("sway".to_string()),
+            ),
+            (
+                &[("GDMSESSION", "custom"), ("XDG_SESSION_DESKTOP", "i3")][..],
+                DesktopEnv::I3,
+            ),
+            (
+                &[("DESKTOP_SESSION", "custom"), ("GDMSESSION", "other")][..],
+                unknown("custom"),
+            ),
+        ] {
+            assert_eq!(
+                desktop_env_from_vars(crate::tests::lookup(vars)),
+                *expected,
+                "{:?}",
+                vars,
+            );
+        }
+    }
+
+    #[test]
+    fn desktop_env_window_manager() {
+        for (session, expected) in &[
+            ("bspwm", "bspwm"),
+            ("dwm", "dwm"),
+            ("awesome", "awesome"),
+            ("qtile", "Qtile"),
+            ("river", "river"),
+            ("Hyprland", "Hyprland"),
+            ("hyprland", "Hyprland"),
+            ("sway", "sway"),
+        ] {
+            assert_eq!(
+                desktop_env_from_session(session),
+                DesktopEnv::WindowManager(expected.to_string()),
+                "{}",
+                session,
+            );
+        }
+
+        assert_eq!(
+            desktop_env_from_session("i3wm"),
+            DesktopEnv::Unknown("i3wm".to_string()),
+        );
+        assert_eq!(
+            DesktopEnv::WindowManager("Qtile".to_string()).to_string(),
+            "Qtile",
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn hyprland_signature() {
+        let commit = "4e242d086e20b32951fdc0ebcbfb4d41b5be8dcc";
+
+        for (signature, expected) in &[
+            (format!("{}_1700000000_1234567890", commit), Some(commit)),
+            (format!("{}_", commit), Some(commit)),
+            (format!("{}_1700000000", &commit[..7]), None),
+            (format!("{}_1700000000", commit.replace('e', "g")), None),
+            (commit.to_string(), None),
+            (String::new(), None),
+        ] {
+            assert_eq!(
+                hyprland_commit(signature),
+                *expected,
+                "{:?}",
+                signature,
+            );
+        }
+    }
+
+    #[cfg(all(feature = "compositor-ipc", not(target_os = "macos")))]
+    #[test]
+    fn json_field() {
+        for (json, expected) in &[
+            (
+                r#"{"version": "0.41.2", "commit": "4e242d0"}"#,
+                Some("0.41.2"),
+            ),
+            ("{\n  \"version\" :\"1.9\"\n}\n", Some("1.9")),
+            (
+                r#"{"branch": "main", "version": "v0.41.2-b"}"#,
+                Some("v0.41.2-b"),
+            ),
+            // Escaped quotes
+            (r#"{"version": "0.41\"2"}"#, None),
+            (
+                r#"{"tag": "say \"version\": \"x\"", "version": "1.0"}"#,
+                Some("1.0"),
+            ),
+            // Nested objects
+            (
+                r#"{"tag": {"version": "x"}, "version": "1.2"}"#,
+                Some("1.2"),
+            ),
+            (
+                r#"{"flags": ["version", "debug"], "version": "1.3"}"#,
+                Some("1.3"),
+            ),
+            (r#"{"tag": {"version": "x"}}"#, None),
+            // Missing, empty or not a string
+            (r#"{"commit": "4e242d0"}"#, None),
+            (r#"{"version": ""}"#, None),
+            (r#"{"version": 3}"#, None),
+            (r#"{"version": "1.0"#, None),
+            ("", None),
+        ] {
+            assert_eq!(
+                json_str_field(json, "version").as_deref(),
+                *expected,
+                "{}",
+                json,
+            );
+        }
+    }
+
+    #[test]
+    fn gecos_name() {
+        for (gecos, expected) in &[
+            ("", ""),
+            ("Jane Doe", "Jane Doe"),
+            ("Doe, Jane", "Doe, Jane"),
+            ("Doe, Jane, PhD", "Doe, Jane, PhD"),
+            ("Jane,Room1,,", "Jane"),
+            ("Jane Doe,Room1,555-0100,555-0199", "Jane Doe"),
+            ("Jane Doe,Room1,555-0100,555-0199,other", "Jane Doe"),
+            (",Room1,555-0100,555-0199", ""),
+            (",,,", ""),
+            ("山田 太郎", "山田 太郎"),
+            ("山田,太郎", "山田,太郎"),
+            // `&` (the capitalized username) is left for the caller
+            ("&", "&"),
+            ("&,Room1,,", "&"),
+        ] {
+            let len = gecos_name_len(gecos.as_bytes());
+
+            assert_eq!(&gecos[..len], *expected, "{:?}", gecos);
+        }
+    }
+
+    #[test]
+    fn hostname_var() {
+        for (hostname, vars, expected) in &[
+            ("", &[("HOSTNAME", "container")][..], "container"),
+            ("host", &[("HOSTNAME", "container")][..], "host"),
+            ("host", &[][..], "host"),
+            ("", &[("HOSTNAME", "")][..], ""),
+            ("", &[][..], ""),
+        ] {
+            assert_eq!(
+                hostname_or_var(
+                    hostname.to_string(),
+                    crate::tests::lookup(vars)
+                ),
+                *expected,
+                "{:?} {:?}",
+                hostname,
+                vars,
+            );
+        }
+    }
+
+    #[test]
+    fn gecos_not_utf8() {
+        // "José García" in ISO-8859-1
+        let gecos = b"Jos\xe9 Garc\xeda,Room1,,".to_vec();
+        let realname = realname_from_gecos(gecos);
+
+        assert_eq!(realname.clone().into_vec(), b"Jos\xe9 Garc\xeda".to_vec());
+        assert_eq!(
+            conversions::string_from_os(realname).unwrap_err().kind(),
+            ErrorKind::InvalidData,
+        );
+        assert_eq!(
+            conversions::string_from_os(realname_from_gecos(
+                "José García,Room1,,".into(),
+            ))
+            .unwrap(),
+            "José García",
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn os_release_codename() {
+        let debian = "PRETTY_NAME=\"Debian GNU/Linux 12 (bookworm)\"\n\
+                      VERSION_ID=\"12\"\n\
+                      VERSION_CODENAME=bookworm\n\
+                      ID=debian\n";
+        let ubuntu = "PRETTY_NAME=\"Ubuntu 22.04.3 LTS\"\n\
+                      VERSION=\"22.04.3 LTS (Jammy Jellyfish)\"\n\
+                      VERSION_CODENAME=jammy\n\
+                      UBUNTU_CODENAME=jammy\n";
+        let fedora = "NAME=\"Fedora Linux\"\n\
+                      VERSION=\"39 (Workstation Edition)\"\n\
+                      VERSION_CODENAME=\"\"\n\
+                      PRETTY_NAME=\"Fedora Linux 39 (Workstation Edition)\"\n";
+
+        assert_eq!(
+            distro_info_os_release(debian).version_codename.as_deref(),
+            Some("bookworm"),
+        );
+        assert_eq!(
+            distro_info_os_release(ubuntu).version_codename.as_deref(),
+            Some("jammy"),
+        );
+        assert_eq!(distro_info_os_release(fedora).version_codename, None);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn gentoo() {
+        let os_release = "NAME=Gentoo\n\
+                          ID=gentoo\n\
+                          PRETTY_NAME=\"Gentoo Linux\"\n\
+                          ANSI_COLOR=\"1;32\"\n\
+                          HOME_URL=\"https://www.gentoo.org/\"\n";
+        let profile = "../../var/db/repos/gentoo/profiles/default/linux/amd64/\
+                       23.0/desktop";
+        let release = "Gentoo Base System release 2.14\n";
+
+        assert_eq!(
+            distro_gentoo(os_release, Some(profile), Some(release)).as_deref(),
+            Some("Gentoo Linux, profile default/linux/amd64/23.0/desktop"),
+        );
+        assert_eq!(
+            distro_gentoo(os_release, None, Some(release)).as_deref(),
+            Some("Gentoo Linux 2.14"),
+        );
+        assert_eq!(
+            distro_gentoo(os_release, Some("/etc/portage/profile"), None),
+            None,
+        );
+        assert_eq!(
+            distro_gentoo("ID=gentoo\nVERSION_ID=2.14\n", None, Some(release)),
+            None,
+        );
+        assert_eq!(
+            distro_gentoo("ID=debian\n", Some(profile), Some(release)),
+            None,
+        );
+    }
+
+    #[test]
+    fn pretty_hostname() {
+        let hostname = || Ok(OsString::from("janes-laptop"));
+
+        for (machine_info, expected) in &[
+            (Some("PRETTY_HOSTNAME=\"Jane's Laptop\"\n"), "Jane's Laptop"),
+            (Some("PRETTY_HOSTNAME='Laptop'\r\n"), "Laptop"),
+            (Some("CHASSIS=laptop\nPRETTY_HOSTNAME=Laptop\n"), "Laptop"),
+            (Some("PRETTY_HOSTNAME=\"\"\n"), "janes-laptop"),
+            (Some("PRETTY_HOSTNAME=\n"), "janes-laptop"),
+            (Some("CHASSIS=laptop\n"), "janes-laptop"),
+            (Some(""), "janes-laptop"),
+            (None, "janes-laptop"),
+        ] {
+            let machine_info = machine_info.map(str::as_bytes);
+
+            assert_eq!(
+                pretty_hostname_or(machine_info, hostname).unwrap(),
+                *expected,
+                "{:?}",
+                machine_info,
+            );
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn lsb_release() {
+        for (lsb_release, expected) in &[
+            (
+                "DISTRIB_ID=Ubuntu\nDISTRIB_RELEASE=22.04\n\
+                 DISTRIB_CODENAME=jammy\n\
+                 DISTRIB_DESCRIPTION=\"Ubuntu 22.04.3 LTS\"\n",
+                Some("Ubuntu 22.04.3 LTS"),
+            ),
+            (
+                "DISTRIB_ID=LinuxMint\nDISTRIB_RELEASE=21.2\n",
+                Some("LinuxMint 21.2"),
+            ),
+            (
+                "DISTRIB_ID=Ubuntu\nDISTRIB_RELEASE=\"Ubuntu 22.04\"\n",
+                Some("Ubuntu 22.04"),
+            ),
+            ("DISTRIB_ID=Arch\n", Some("Arch")),
+            ("DISTRIB_RELEASE=22.04\n", None),
+            ("", None),
+        ] {
+            assert_eq!(
+                distro_lsb_release(lsb_release).as_deref(),
+                *expected,
+                "{:?}",
+                lsb_release,
+            );
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn chromeos_lsb_release() {
+        let lsb_release =
+            "CHROMEOS_AUSERVER=https://tools.google.com/service/update2
+CHROMEOS_DEVSERVER=
+CHROMEOS_RELEASE_BOARD=octopus-signed-mp-v23keys
+CHROMEOS_RELEASE_BRANCH_NUMBER=48
+CHROMEOS_RELEASE_BUILD_NUMBER=15786
+CHROMEOS_RELEASE_BUILD_TYPE=Official Build
+CHROMEOS_RELEASE_CHROME_MILESTONE=123
+CHROMEOS_RELEASE_DESCRIPTION=15786.48.0 (Official Build) stable-channel octopus
+CHROMEOS_RELEASE_NAME=Chrome OS
+CHROMEOS_RELEASE_TRACK=stable-channel
+CHROMEOS_RELEASE_VERSION=15786.48.0
+DEVICETYPE=CHROMEBOOK
+GOOGLE_RELEASE=15786.48.0
+";
+
+        assert_eq!(
+            distro_chromeos(lsb_release).as_deref(),
+            Some("Chrome OS 15786.48.0"),
+        );
+
+        // `GOOGLE_RELEASE` takes precedence, wherever it is
+        let description_last = "GOOGLE_RELEASE=15786.48.0\n\
+            CHROMEOS_RELEASE_DESCRIPTION=15786.48.0 (Official Build)\n";
+
+        assert_eq!(
+            distro_chromeos(description_last).as_deref(),
+            Some("Chrome OS 15786.48.0"),
+        );
+
+        let description_only = lsb_release.replace("GOOGLE_RELEASE", "IGNORED");
+
+        assert_eq!(
+            distro_chromeos(&description_only).as_deref(),
+            Some(
+                "Chrome OS 15786.48.0 (Official Build) stable-channel octopus"
+            ),
+        );
+
+        // Other distributions' `/etc/lsb-release`
+        assert_eq!(
+            distro_chromeos("DISTRIB_ID=Ubuntu\nDISTRIB_RELEASE=22.04\n"),
+            None,
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn alpine_release() {
+        let minimal = "NAME=\"Alpine Linux\"\nID=alpine\n";
+        let full = "NAME=\"Alpine Linux\"\nID=alpine\nVERSION_ID=3.19.1\n\
+                    PRETTY_NAME=\"Alpine Linux v3.19\"\n";
+
+        for (os_release, alpine_release, expected) in &[
+            (Some(minimal), "3.19.1\n", Some("Alpine Linux 3.19.1")),
+            (None, "3.19.1\n", Some("Alpine Linux 3.19.1")),
+            (Some("ID=alpine\n"), "3.18.4", Some("Alpine Linux 3.18.4")),
+            (Some(full), "3.19.1\n", None),
+            (Some("ID=debian\n"), "3.19.1\n", None),
+            (Some(minimal), "\n", None),
+        ] {
+            assert_eq!(
+                distro_alpine_release(*os_release, alpine_release).as_deref(),
+                *expected,
+                "{:?}",
+                os_release,
+            );
+        }
+
+        // The full os-release is used as is
+        assert_eq!(
+            distro_os_release(full).as_deref(),
+            Some("Alpine Linux v3.19"),
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn release_file_error() {
+        let error =
+            read_release_file("/nonexistent/etc/os-release").unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+        assert!(
+            error
+                .to_string()
+                .starts_with("Failed to read /nonexistent/etc/os-release: "),
+            "{}",
+            error,
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn legacy_release_files() {
+        assert_eq!(
+            distro_redhat_release("CentOS release 6.10 (Final)\n").as_deref(),
+            Some("CentOS release 6.10 (Final)"),
+        );
+        assert_eq!(
+            distro_redhat_release(
+                "\nRed Hat Enterprise Linux Server release 7.9 (Maipo)\n"
+            )
+            .as_deref(),
+            Some("Red Hat Enterprise Linux Server release 7.9 (Maipo)"),
+        );
+        assert_eq!(distro_redhat_release(" \n"), None);
+        assert_eq!(
+            distro_debian_version("12.5\n").as_deref(),
+            Some("Debian 12.5"),
+        );
+        assert_eq!(
+            distro_debian_version("trixie/sid\n").as_deref(),
+            Some("Debian trixie/sid"),
+        );
+        assert_eq!(distro_debian_version(""), None);
+    }
+
+    #[test]
+    fn arch_machine_alias() {
+        // Machine names that no other test uses, since aliases are global
+        assert_eq!(
+            arch_from_machine("whoami-alias-test"),
+            Arch::Unknown("whoami-alias-test".to_string()),
+        );
+
+        crate::register_arch_alias("whoami-alias-test", Arch::Sparc64);
+
+        assert_eq!(arch_from_machine("whoami-alias-test"), Arch::Sparc64);
+        assert_eq!(arch_from_machine("WHOAMI-ALIAS-TEST"), Arch::Sparc64);
+
+        crate::register_arch_alias("Whoami-Alias-Test", Arch::Riscv64);
+
+        assert_eq!(arch_from_machine("whoami-alias-test"), Arch::Riscv64);
+        assert_eq!(
+            arch_from_machine("whoami-alias-other"),
+            Arch::Unknown("whoami-alias-other".to_string()),
+        );
+    }
+
+    #[test]
+    fn arch_machine() {
+        for (machine, arch) in &[
+            ("sparc", Arch::Sparc),
+            ("sparc64", Arch::Sparc64),
+            ("sparcv9", Arch::Sparc64),
+            ("sun4u", Arch::Sparc64),
+            ("sun4v", Arch::Sparc64),
+            ("x86_64", Arch::X64),
+            ("amd64", Arch::X64),
+            ("AMD64", Arch::X64),
+            ("X86_64", Arch::X64),
+            ("i86pc", Arch::X64),
+            ("x86", Arch::I686),
+            ("X86", Arch::I686),
+            ("x86pc", Arch::I686),
+            ("i686", Arch::I686),
+            ("i686-AT386", Arch::I686),
+            ("I386", Arch::I386),
+            ("e2k", Arch::E2k),
+            ("E2K", Arch::E2k),
+            ("m68k", Arch::M68k),
+            ("riscv128", Arch::Riscv128),
+            ("sh4", Arch::Sh4),
+            ("sh", Arch::Sh4),
+            ("SH4", Arch::Sh4),
+            ("ppc", Arch::PowerPc),
+            ("powerpc", Arch::PowerPc),
+            ("ppcle", Arch::PowerPcLe),
+            ("powerpcle", Arch::PowerPcLe),
+            ("ppc64", Arch::PowerPc64),
+            ("ppc64le", Arch::PowerPc64Le),
+            ("powerpc64le", Arch::PowerPc64Le),
+        ] {
+            assert_eq!(arch_from_machine(machine), *arch, "{}", machine);
+        }
+
+        assert_eq!(
+            arch_from_machine("sun4m"),
+            Arch::Unknown("sun4m".to_string()),
+        );
+    }
+
+    /// A `SystemVersion.plist` from macOS 14.2.1, in the binary format
+    #[cfg(target_os = "macos")]
+    const SYSTEM_VERSION_BPLIST: &[u8] = &[
+        0x62, 0x70, 0x6c, 0x69, 0x73, 0x74, 0x30, 0x30, 0xd6, 0x01, 0x02, 0x03,
+        0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0a, 0x0b, 0x5f, 0x10, 0x13,
+        0x50, 0x72, 0x6f, 0x64, 0x75, 0x63, 0x74, 0x42, 0x75, 0x69, 0x6c, 0x64,
+        0x56, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e, 0x5f, 0x10, 0x10, 0x50, 0x72,
+        0x6f, 0x64, 0x75, 0x63, 0x74, 0x43, 0x6f, 0x70, 0x79, 0x72, 0x69, 0x67,
+        0x68, 0x74, 0x5b, 0x50, 0x72, 0x6f, 0x64, 0x75, 0x63, 0x74, 0x4e, 0x61,
+        0x6d, 0x65, 0x5f, 0x10, 0x19, 0x50, 0x72, 0x6f, 0x64, 0x75, 0x63, 0x74,
+        0x55, 0x73, 0x65, 0x72, 0x56, 0x69, 0x73, 0x69, 0x62, 0x6c, 0x65, 0x56,
+        0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e, 0x5e, 0x50, 0x72, 0x6f, 0x64, 0x75,
+        0x63, 0x74, 0x56, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e, 0x5f, 0x10, 0x11,
+        0x69, 0x4f, 0x53, 0x53, 0x75, 0x70, 0x70, 0x6f, 0x72, 0x74, 0x56, 0x65,
+        0x72, 0x73, 0x69, 0x6f, 0x6e, 0x55, 0x32, 0x33, 0x43, 0x37, 0x31, 0x5f,
+        0x10, 0x14, 0x31, 0x39, 0x38, 0x33, 0x2d, 0x32, 0x30, 0x32, 0x33, 0x20,
+        0x41, 0x70, 0x70, 0x6c, 0x65, 0x20, 0x49, 0x6e, 0x63, 0x2e, 0x55, 0x6d,
+        0x61, 0x63, 0x4f, 0x53, 0x56, 0x31, 0x34, 0x2e, 0x32, 0x2e, 0x31, 0x54,
+        0x31, 0x37, 0x2e, 0x32, 0x08, 0x15, 0x2b, 0x3e, 0x4a, 0x66, 0x75, 0x89,
+        0x8f, 0xa6, 0xac, 0xb3, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xb8,
+    ];
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn bplist_system_version() {
+        let entries = bplist_strings(SYSTEM_VERSION_BPLIST).unwrap();
+
+        assert_eq!(entries.len(), 6, "{:?}", entries);
+        assert!(entries.contains(&("ProductName".into(), "macOS".into())));
+        assert!(entries
+            .contains(&("ProductUserVisibleVersion".into(), "14.2.1".into())));
+        assert!(entries.contains(&(
+            "ProductCopyright".into(),
+            "1983-2023 Apple Inc.".into()
+        )));
+        assert_eq!(
+            distro_plist(SYSTEM_VERSION_BPLIST.to_vec()).unwrap(),
+            "macOS 14.2.1",
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn bplist_truncated() {
+        for len in 0..SYSTEM_VERSION_BPLIST.len() {
+            // Must not panic, and the trailer is gone
+            assert_eq!(
+                bplist_strings(&SYSTEM_VERSION_BPLIST[..len]),
+                None,
+                "{}",
+                len,
+            );
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn bplist_corrupt() {
+        for index in 0..SYSTEM_VERSION_BPLIST.len() {
+            for &byte in &[0x00, 0x0F, 0x7F, 0xFF] {
+                let mut data = SYSTEM_VERSION_BPLIST.to_vec();
+
+                data[index] = byte;
+
+                // Must not panic
+                let _ = bplist_strings(&data);
+            }
+        }
+
+        // The top object isn't a dictionary
+        let mut data = SYSTEM_VERSION_BPLIST.to_vec();
+
+        data[8] = 0xA6;
+
+        assert_eq!(bplist_strings(&data), None);
+        assert_eq!(
+            distro_plist(data).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn xml_interleaved() {
+        let plist = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>ProductName</key>
+	<data>
+	bWFjT1M=
+	</data>
+	<key>ProductBuildVersion</key>
+	<string>23C71</string>
+	<key>ProductUserVisibleVersion</key>
+	<array>
+		<string>13.0</string>
+	</array>
+	<key>ProductVersion</key>
+	<true/>
+	<key>ProductCopyright</key>
+	<string>1983-2023 Apple Inc.</string>
+	<key>ProductName</key>
+	<string>macOS</string>
+	<key>ProductVersion</key>
+	<string>14.2.1</string>
+</dict>
+</plist>
+"#;
+
+        assert_eq!(distro_xml(plist.to_string()).unwrap(), "macOS 14.2.1");
+
+        // A tracked key whose only value isn't a string
+        let plist = "<dict>\n<key>ProductName</key>\n<data>\n</data>\n\
+            <key>ProductBuildVersion</key>\n<string>23C71</string>\n</dict>";
+
+        assert_eq!(
+            distro_xml(plist.to_string()).unwrap_err().kind(),
+            ErrorKind::InvalidData,
+        );
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn rosetta() {
+        for (arch, translated, expected) in &[
+            (Arch::X64, Some(1), Arch::Arm64),
+            (Arch::X64, Some(0), Arch::X64),
+            (Arch::X64, None, Arch::X64),
+            (Arch::Arm64, Some(0), Arch::Arm64),
+            (Arch::Arm64, None, Arch::Arm64),
+        ] {
+            assert_eq!(
+                &rosetta_arch(arch.clone(), *translated),
+                expected,
+                "{:?}",
+                (arch, translated),
+            );
+        }
+    }
+
+    #[test]
+    fn libc_linkers() {
+        let alpine = ["ld-linux-x86-64.so.2", "ld-musl-x86_64.so.1"];
+        let debian = ["libc.so.6", "ld-linux-x86-64.so.2"];
+
+        assert_eq!(libc_from_linkers(alpine.iter()), Libc::Musl);
+        assert_eq!(libc_from_linkers(alpine.iter().rev()), Libc::Musl);
+        assert_eq!(libc_from_linkers(debian.iter()), Libc::Glibc);
+        assert_eq!(libc_from_linkers(["libc.so"].iter()), Libc::Unknown);
+    }
+
+    #[test]
+    fn language_lists() {
+        let tags = |list| {
+            langs_from_list(list)
+                .into_iter()
+                .map(|l| l.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        for (list, expected) in &[
+            ("de_DE:de:en", &["de-DE", "de", "en"][..]),
+            ("de_DE:fr_FR", &["de-DE", "de", "fr-FR", "fr"][..]),
+            ("en_US.UTF-8:en_GB", &["en-US", "en", "en-GB"][..]),
+            ("sr_RS@latin:sr", &["sr-RS", "sr"][..]),
+            ("fr:C", &["fr"][..]),
+            ("C:fr_CA", &["fr-CA", "fr"][..]),
+            ("POSIX:C.UTF-8:nl", &["nl"][..]),
+            ("es::en", &["es", "en"][..]),
+            ("C", &[][..]),
+            ("POSIX", &[][..]),
+            ("", &[][..]),
+        ] {
+            assert_eq!(tags(list), *expected, "{:?}", list);
+        }
+    }
+
+    #[test]
+    fn preferred_languages() {
+        for (vars, expected) in &[
+            (&[][..], &["en-US", "en"][..]),
+            (&[("LANG", "")], &["en-US", "en"]),
+            (
+                &[("LANG", "C.UTF-8"), ("LANGUAGE", "fr:de")],
+                &["en-US", "en"],
+            ),
+            (&[("LANG", "de_DE.UTF-8")], &["de-DE", "de"]),
+            (&[("LC_ALL", "pt_BR"), ("LANG", "de_DE")], &["pt-BR", "pt"]),
+            (&[("LC_MESSAGES", "fr"), ("LANG", "de_DE")], &["fr"]),
+            (
+                &[("LANG", "de_DE.UTF-8"), ("LANGUAGE", "fr_CA:de_DE:en")],
+                &["fr-CA", "fr", "de-DE", "de", "en"],
+            ),
+            (
+                &[("LANG", "de_DE.UTF-8"), ("LANGUAGE", "C")],
+                &["en-US", "en"],
+            ),
+        ] {
+            let tags: Vec<String> = langs_from_vars(crate::tests::lookup(vars))
+                .into_iter()
+                .map(|language| language.to_string())
+                .collect();
+
+            assert_eq!(tags, *expected, "{:?}", vars);
+        }
+    }
+
+    #[test]
+    fn first_lang() {
+        let tag = |list| first_lang_from_list(list).map(|l| l.to_string());
+
+        assert_eq!(tag("en_US.UTF-8").as_deref(), Some("en-US"));
+        assert_eq!(tag("de_DE:fr_FR:en").as_deref(), Some("de-DE"));
+        assert_eq!(tag(":pt_BR:pt").as_deref(), Some("pt-BR"));
+        assert_eq!(tag("C:POSIX.UTF-8:it_IT").as_deref(), Some("it-IT"));
+        assert_eq!(tag("C"), None);
+        assert_eq!(tag(""), None);
+
+        for list in &["en_US.UTF-8", "de_DE:fr_FR:en", ":pt_BR:pt", "C:it"] {
+            assert_eq!(
+                first_lang_from_list(list),
+                langs_from_list(list).into_iter().next(),
+            );
+        }
+    }
+
+    #[cfg(target_os = "illumos")]
+    #[test]
+    fn nodename_file() {
+        for (data, expected) in &[
+            ("myhost\n", Some("myhost")),
+            ("  myhost  \n", Some("myhost")),
+            ("\"myhost\"\n", Some("myhost")),
+            ("'myhost'\n", Some("myhost")),
+            ("\" myhost \"\n", Some("myhost")),
+            ("# Set by the installer\n\nmyhost\n", Some("myhost")),
+            ("myhost # The primary interface\n", Some("myhost")),
+            ("myhost\nother\n", Some("myhost")),
+            ("# Only a comment\n", None),
+            ("\"\"\n", None),
+            ("", None),
+        ] {
+            assert_eq!(nodename_from_file(data), *expected, "{:?}", data);
+        }
+    }
+
+    #[cfg(any(
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    #[test]
+    fn bsd_devicename() {
+        let devicename = Os.devicename().unwrap();
+
+        assert!(!devicename.is_empty());
+
+        // Unless `/etc/machine-info` sets a "pretty" name
+        if read_devicename_from_machine_info().is_err() {
+            assert_eq!(devicename, gethostname().unwrap());
+        }
+
+        #[cfg(not(target_os = "openbsd"))]
+        {
+            assert_eq!(
+                sysctl_string("kern.hostname").unwrap(),
+                gethostname().unwrap().to_string_lossy(),
+            );
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dmi() {
+        for (data, expected) in &[
+            (
+                "ThinkPad X1 Carbon Gen 11\n",
+                Some("ThinkPad X1 Carbon Gen 11"),
+            ),
+            ("LENOVO\n", Some("LENOVO")),
+            (
+                "  Standard PC (Q35 + ICH9, 2009)  \n",
+                Some("Standard PC (Q35 + ICH9, 2009)"),
+            ),
+            ("To Be Filled By O.E.M.\n", None),
+            ("To be filled by O.E.M.\n", None),
+            ("System Product Name\n", None),
+            ("System manufacturer\n", None),
+            ("Default string\n", None),
+            ("\n", None),
+            ("", None),
+        ] {
+            assert_eq!(dmi_value(data), *expected, "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn hosts() {
+        let hosts = "\
+# 127.0.1.1 myhost.commented.example myhost
+127.0.0.1\tlocalhost localhost.localdomain
+::1 localhost ip6-localhost ip6-loopback
+
+# The short name comes before the FQDN
+127.0.1.1 myhost myhost.lan.example. # myhost.trailing.example
+10.0.0.2 other other.example.com
+10.0.0.3 alias www.example.com Web.Example.com
+";
+
+        for (hostname, expected) in &[
+            ("myhost", Some("myhost.lan.example")),
+            ("MyHost", Some("myhost.lan.example")),
+            ("other", Some("other.example.com")),
+            // An alias, after other names on the line
+            ("web", Some("Web.Example.com")),
+            ("www", Some("www.example.com")),
+            ("localhost", Some("localhost.localdomain")),
+            ("alias", None),
+            ("missing", None),
+        ] {
+            assert_eq!(
+                hosts_fqdn(hosts, hostname).as_deref(),
+                *expected,
+                "{:?}",
+                hostname,
+            );
+        }
+
+        assert_eq!(hosts_fqdn("127.0.1.1 myhost myhost.\n", "myhost"), None);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn mountinfo() {
+        let mountinfo = "\
+22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+23 22 0:21 / /proc rw,nosuid,nodev,noexec,relatime shared:12 - proc proc rw
+24 22 0:5 / /dev rw,nosuid shared:2 master:1 - devtmpfs devtmpfs rw
+40 22 0:35 / /home rw,relatime shared:20 - btrfs /dev/sda1 rw,subvol=/home
+41 40 0:36 / /home/user/My\\040Drive rw,nosuid - fuse.rclone drive: rw
+42 40 0:37 / /home/user/My rw - tmpfs tmpfs rw
+43 22 0:38 / /mnt rw - nfs4 server:/export rw
+44 22 0:39 / /mnt rw shared:30 propagate_from:2 unbindable - cifs //nas/share rw
+bad line
+";
+
+        for (path, expected) in &[
+            ("/", Some("ext4")),
+            ("/etc/hostname", Some("ext4")),
+            ("/proc/self", Some("proc")),
+            ("/dev", Some("devtmpfs")),
+            ("/home/user", Some("btrfs")),
+            // A longer mount point than `/home`, but not a prefix
+            ("/homework", Some("ext4")),
+            ("/home/user/My Drive/notes.txt", Some("fuse.rclone")),
+            ("/home/user/My/notes.txt", Some("tmpfs")),
+            // The later mount on the same mount point, with optional fields
+            ("/mnt/share", Some("cifs")),
+        ] {
+            assert_eq!(
+                mountinfo_fs_type(mountinfo, Path::new(path)).as_deref(),
+                *expected,
+                "{:?}",
+                path,
+            );
+        }
+
+        assert_eq!(mountinfo_fs_type("", Path::new("/")), None);
+        assert_eq!(mountinfo_unescape(r"a\040b\134c\011"), "a b\\c\t");
+        assert_eq!(mountinfo_unescape(r"a\04b\"), r"a\04b\");
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn cgroup_quota() {
+        assert_eq!(cgroup_v2_cpus("max 100000\n"), None);
+        assert_eq!(cgroup_v2_cpus("200000 100000\n"), Some(2));
+        assert_eq!(cgroup_v2_cpus("150000 100000\n"), Some(1));
+        assert_eq!(cgroup_v2_cpus("50000 100000\n"), Some(1));
+        assert_eq!(cgroup_v2_cpus("400000"), Some(4));
+        assert_eq!(cgroup_v2_cpus(""), None);
+        assert_eq!(cgroup_v1_cpus("-1\n", "100000\n"), None);
+        assert_eq!(cgroup_v1_cpus("300000\n", "100000\n"), Some(3));
+        assert_eq!(cgroup_v1_cpus("250000\n", "100000\n"), Some(2));
+        assert_eq!(cgroup_v1_cpus("100000\n", "0\n"), None);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn cgroup_path() {
+        let v2 = "0::/user.slice/user-1000.slice/session-2.scope\n";
+        let v1 = "12:pids:/user.slice/user-1000.slice\n\
+            4:cpu,cpuacct:/docker/0123abcd\n\
+            1:name=systemd:/docker/0123abcd\n\
+            0::/docker/0123abcd\n";
+
+        assert_eq!(
+            proc_cgroup_path(v2, None),
+            Some("/user.slice/user-1000.slice/session-2.scope"),
+        );
+        assert_eq!(proc_cgroup_path(v2, Some("cpu")), None);
+        assert_eq!(proc_cgroup_path(v1, Some("cpu")), Some("/docker/0123abcd"));
+        assert_eq!(
+            proc_cgroup_path(v1, Some("cpuacct")),
+            Some("/docker/0123abcd")
+        );
+        assert_eq!(proc_cgroup_path(v1, Some("cpuset")), None);
+        assert_eq!(proc_cgroup_path(v1, None), Some("/docker/0123abcd"));
+        assert_eq!(proc_cgroup_path("", None), None);
+
+        // The limits of the ancestors apply, relative to the mount point
+        let limits = |cgroup: &Path| match cgroup.to_str()? {
+            "" => Some(8),
+            "docker" => None,
+            "docker/0123abcd" => Some(2),
+            "system.slice" => Some(4),
+            _ => None,
+        };
+
+        assert_eq!(cgroup_min_limit("/docker/0123abcd", limits), Some(2));
+        assert_eq!(cgroup_min_limit("/docker/other", limits), Some(8));
+        assert_eq!(
+            cgroup_min_limit("/system.slice/a.service", limits),
+            Some(4)
+        );
+        assert_eq!(cgroup_min_limit("/", limits), Some(8));
+        assert_eq!(cgroup_min_limit("/", |_| None), None);
     }
 }
@@ -1,13 +1,17 @@
 #[cfg(not(any(target_pointer_width = "32", target_pointer_width = "64")))]
 compile_error!("Unexpected pointer width for target platform");
 
-use std::{env, ffi::OsString};
+use std::{env, ffi::OsString, fs, os::raw::c_int};
 
 use crate::{
-    os::{Os, Target},
-    Arch, DesktopEnv, Language, Platform, Result,
+    conversions,
+    os::{unsupported, Os, Target},
+    Arch, DesktopEnv, DistroInfo, GroupId, Language, Libc, OutputKind,
+    Platform, Result, UnameInfo, UserId,
 };
 
+const OS_RELEASE: &str = "/etc/os-release";
+
 #[inline(always)]
 pub(crate) fn lang() -> impl Iterator<Item = String> {
     let langs: Vec<String> = wasite::langs()
@@ -19,9 +23,23 @@ pub(crate) fn lang() -> impl Iterator<Item = String> {
     langs.into_iter()
 }
 
+/// Get the distribution from the contents of `/etc/os-release`, if it could be
+/// read, or else "Unknown WASI".
+fn distro_or_unknown(os_release: Option<&str>) -> String {
+    os_release
+        .and_then(conversions::distro_os_release)
+        .unwrap_or_else(|| "Unknown WASI".to_string())
+}
+
 impl Target for Os {
     fn langs(self) -> Vec<Language> {
-        todo!()
+        lang()
+            .filter_map(|lang| conversions::language_from_locale(&lang))
+            .collect()
+    }
+
+    fn primary_lang(self) -> Option<Language> {
+        lang().find_map(|lang| conversions::language_from_locale(&lang))
     }
 
     #[inline(always)]
@@ -38,6 +56,26 @@ impl Target for Os {
             .into())
     }
 
+    #[inline(always)]
+    fn user(self) -> Result<(OsString, OsString)> {
+        Ok((self.username()?, Os.realname()?))
+    }
+
+    #[inline(always)]
+    fn login(self) -> Result<OsString> {
+        self.username()
+    }
+
+    #[inline(always)]
+    fn uid(self) -> Result<UserId> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn gid(self) -> Result<GroupId> {
+        Err(unsupported())
+    }
+
     #[inline(always)]
     fn devicename(self) -> Result<OsString> {
         Ok(wasite::name()
@@ -45,14 +83,67 @@ impl Target for Os {
             .into())
     }
 
+    #[inline(always)]
+    fn pretty_hostname(self) -> Result<OsString> {
+        self.devicename()
+    }
+
+    #[inline(always)]
+    fn local_hostname(self) -> Result<OsString> {
+        Err(unsupported())
+    }
+
     #[inline(always)]
     fn hostname(self) -> Result<String> {
         Ok(wasite::hostname().unwrap_or_else(|_e| "localhost".to_string()))
     }
 
-    #[inline(always)]
     fn distro(self) -> Result<String> {
-        Ok("Unknown WASI".to_string())
+        // Only readable if the host preopened `/etc`
+        Ok(distro_or_unknown(
+            fs::read_to_string(OS_RELEASE).ok().as_deref(),
+        ))
+    }
+
+    fn distro_info(self) -> Result<DistroInfo> {
+        let data = fs::read_to_string(OS_RELEASE)?;
+
+        Ok(conversions::distro_info_os_release(&data))
+    }
+
+    #[inline(always)]
+    fn kernel_version(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn uname_info(self) -> Result<UnameInfo> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn model_identifier(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn device_model(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn device_vendor(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn cwd_fs_type(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn fqdn_local(self, _hostname: &str) -> Result<String> {
+        Err(unsupported())
     }
 
     #[inline(always)]
@@ -64,9 +155,24 @@ impl Target for Os {
         }
     }
 
+    #[inline(always)]
+    fn window_manager(self) -> Option<String> {
+        None
+    }
+
+    #[inline(always)]
+    fn desktop_env_version(self) -> Option<String> {
+        None
+    }
+
     #[inline(always)]
     fn platform(self) -> Platform {
-        Platform::Unknown("WASI".to_string())
+        Platform::Wasi
+    }
+
+    #[inline(always)]
+    fn platform_detected(self) -> bool {
+        true
     }
 
     #[inline(always)]
@@ -77,4 +183,68 @@ impl Target for Os {
             Arch::Wasm32
         })
     }
+
+    #[inline(always)]
+    fn native_arch(self) -> Result<Arch> {
+        self.arch()
+    }
+
+    #[inline(always)]
+    fn isatty(self, _fd: c_int) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn output_kind(self, _fd: c_int) -> OutputKind {
+        OutputKind::Redirected
+    }
+
+    #[inline(always)]
+    fn libc(self) -> Libc {
+        Libc::Unknown
+    }
+
+    #[inline(always)]
+    fn available_parallelism(self) -> Option<usize> {
+        None
+    }
+
+    #[inline(always)]
+    fn cpu_count(self) -> Option<usize> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn platform() {
+        assert_eq!(Os.platform(), Platform::Wasi);
+        assert!(Os.platform_detected());
+        assert_eq!(crate::platform(), Platform::Wasi);
+        assert_eq!(crate::platform_family(), crate::PlatformFamily::Wasm);
+    }
+
+    #[test]
+    fn os_release() {
+        assert_eq!(
+            distro_or_unknown(Some(
+                "NAME=\"Alpine Linux\"\nVERSION_ID=3.19.0\n"
+            )),
+            "Alpine Linux 3.19.0",
+        );
+        assert_eq!(distro_or_unknown(Some("ID=alpine\n")), "Unknown WASI");
+        assert_eq!(distro_or_unknown(None), "Unknown WASI");
+
+        // Whether `/etc` is readable depends on the runtime's preopens
+        match fs::read_to_string(OS_RELEASE) {
+            Ok(data) => assert_eq!(
+                Os.distro_info().unwrap(),
+                conversions::distro_info_os_release(&data),
+            ),
+            Err(_) => assert!(Os.distro_info().is_err()),
+        }
+    }
 }
@@ -4,14 +4,18 @@ compile_error!("Unexpected pointer width for target platform");
 use std::{
     ffi::OsString,
     io::{Error, ErrorKind},
+    os::raw::c_int,
 };
 
+use js_sys::Reflect;
 use wasm_bindgen::JsValue;
 use web_sys::window;
 
 use crate::{
-    os::{Os, Target},
-    Arch, DesktopEnv, Language, Platform, Result,
+    conversions,
+    os::{unsupported, Os, Target},
+    Arch, DesktopEnv, DistroInfo, GroupId, Language, Libc, OutputKind,
+    Platform, Result, UnameInfo, UserId,
 };
 
 // Get the user agent
@@ -19,6 +23,18 @@ fn user_agent() -> Option<String> {
     window()?.navigator().user_agent().ok()
 }
 
+// Get the platform from the User-Agent Client Hints API, where supported
+fn user_agent_platform() -> Option<String> {
+    let navigator = window()?.navigator();
+    let data = Reflect::get(&navigator, &JsValue::from_str("userAgentData"))
+        .ok()
+        .filter(JsValue::is_object)?;
+
+    Reflect::get(&data, &JsValue::from_str("platform"))
+        .ok()?
+        .as_string()
+}
+
 // Get the document domain
 fn document_domain() -> Option<String> {
     window()?.document()?.location()?.hostname().ok()
@@ -60,7 +76,27 @@ pub(crate) fn lang() -> impl Iterator<Item = String> {
 
 impl Target for Os {
     fn langs(self) -> Vec<Language> {
-        todo!()
+        let navigator = if let Some(window) = window() {
+            window.navigator()
+        } else {
+            return Vec::new();
+        };
+
+        conversions::languages_from_navigator(
+            navigator.languages().iter().map(|lang| lang.as_string()),
+            || navigator.language(),
+        )
+    }
+
+    fn primary_lang(self) -> Option<Language> {
+        let navigator = window()?.navigator();
+
+        navigator
+            .languages()
+            .get(0)
+            .as_string()
+            .or_else(|| navigator.language())
+            .and_then(|lang| conversions::language_from_locale(&lang))
     }
 
     fn realname(self) -> Result<OsString> {
@@ -71,45 +107,43 @@ impl Target for Os {
         Ok("anonymous".to_string().into())
     }
 
+    #[inline(always)]
+    fn user(self) -> Result<(OsString, OsString)> {
+        Ok((self.username()?, Os.realname()?))
+    }
+
+    #[inline(always)]
+    fn login(self) -> Result<OsString> {
+        self.username()
+    }
+
+    #[inline(always)]
+    fn uid(self) -> Result<UserId> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn gid(self) -> Result<GroupId> {
+        Err(unsupported())
+    }
+
     fn devicename(self) -> Result<OsString> {
-        let orig_string = user_agent().unwrap_or_default();
-        let start = if let Some(s) = orig_string.rfind(' ') {
-            s
-        } else {
-            return Ok("Unknown Browser".to_string().into());
-        };
-        let string = orig_string
-            .get(start + 1..)
-            .unwrap_or("Unknown Browser")
-            .replace('/', " ");
-        let string = if let Some(s) = string.rfind("Safari") {
-            if let Some(s) = orig_string.rfind("Chrome") {
-                if let Some(e) =
-                    orig_string.get(s..).unwrap_or_default().find(' ')
-                {
-                    orig_string
-                        .get(s..)
-                        .unwrap_or("Chrome")
-                        .get(..e)
-                        .unwrap_or("Chrome")
-                        .replace('/', " ")
-                } else {
-                    "Chrome".to_string()
-                }
-            } else if orig_string.contains("Linux") {
-                "GNOME Web".to_string()
-            } else {
-                string.get(s..).unwrap_or("Safari").replace('/', " ")
-            }
-        } else if string.contains("Edg ") {
-            string.replace("Edg ", "Edge ")
-        } else if string.contains("OPR ") {
-            string.replace("OPR ", "Opera ")
-        } else {
-            string
-        };
+        let browser = user_agent()
+            .as_deref()
+            .and_then(conversions::browser_from_user_agent)
+            .unwrap_or("Unknown Browser");
 
-        Ok(string.into())
+        Ok(browser.to_string().into())
+    }
+
+    #[inline(always)]
+    fn pretty_hostname(self) -> Result<OsString> {
+        self.devicename()
+    }
+
+    #[inline(always)]
+    fn local_hostname(self) -> Result<OsString> {
+        Err(unsupported())
     }
 
     fn hostname(self) -> Result<String> {
@@ -119,69 +153,53 @@ impl Target for Os {
     }
 
     fn distro(self) -> Result<String> {
-        let string = user_agent()
+        let user_agent = user_agent()
             .ok_or_else(|| Error::from(ErrorKind::PermissionDenied))?;
-        let err = || Error::new(ErrorKind::InvalidData, "Parsing failed");
-        let begin = string.find('(').ok_or_else(err)?;
-        let end = string.find(')').ok_or_else(err)?;
-        let string = &string[begin + 1..end];
-
-        Ok(if string.contains("Win32") || string.contains("Win64") {
-            let begin = if let Some(b) = string.find("NT") {
-                b
-            } else {
-                return Ok("Windows".to_string());
-            };
-            let end = if let Some(e) = string.find('.') {
-                e
-            } else {
-                return Ok("Windows".to_string());
-            };
-            let string = &string[begin + 3..end];
-
-            format!("Windows {}", string)
-        } else if string.contains("Linux") {
-            let string = if string.contains("X11") || string.contains("Wayland")
-            {
-                let begin = if let Some(b) = string.find(';') {
-                    b
-                } else {
-                    return Ok("Unknown Linux".to_string());
-                };
-                &string[begin + 2..]
-            } else {
-                string
-            };
 
-            if string.starts_with("Linux") {
-                "Unknown Linux".to_string()
-            } else {
-                let end = if let Some(e) = string.find(';') {
-                    e
-                } else {
-                    return Ok("Unknown Linux".to_string());
-                };
-                string[..end].to_string()
-            }
-        } else if let Some(begin) = string.find("Mac OS X") {
-            if let Some(end) = string[begin..].find(';') {
-                string[begin..begin + end].to_string()
-            } else {
-                string[begin..].to_string().replace('_', ".")
-            }
-        } else {
-            // TODO:
-            // Platform::FreeBsd,
-            // Platform::Ios,
-            // Platform::Android,
-            // Platform::Nintendo,
-            // Platform::Xbox,
-            // Platform::PlayStation,
-            // Platform::Dive,
-            // Platform::Fuchsia,
-            // Platform::Redox
-            string.to_string()
-        })
+        Ok(conversions::distro_from_user_agent(
+            &user_agent,
+            user_agent_platform(),
+        ))
+    }
+
+    #[inline(always)]
+    fn distro_info(self) -> Result<DistroInfo> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn kernel_version(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn uname_info(self) -> Result<UnameInfo> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn model_identifier(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn device_model(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn device_vendor(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn cwd_fs_type(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn fqdn_local(self, _hostname: &str) -> Result<String> {
+        Err(unsupported())
     }
 
     #[inline(always)]
@@ -189,39 +207,24 @@ impl Target for Os {
         DesktopEnv::WebBrowser
     }
 
+    #[inline(always)]
+    fn window_manager(self) -> Option<String> {
+        None
+    }
+
+    #[inline(always)]
+    fn desktop_env_version(self) -> Option<String> {
+        None
+    }
+
+    #[inline(always)]
     fn platform(self) -> Platform {
-        let string = user_agent().unwrap_or_default();
-        let begin = if let Some(b) = string.find('(') {
-            b
-        } else {
-            return Platform::Unknown("Unknown".to_string());
-        };
-        let end = if let Some(e) = string.find(')') {
-            e
-        } else {
-            return Platform::Unknown("Unknown".to_string());
-        };
-        let string = &string[begin + 1..end];
-
-        if string.contains("Win32") || string.contains("Win64") {
-            Platform::Windows
-        } else if string.contains("Linux") {
-            Platform::Linux
-        } else if string.contains("Mac OS X") {
-            Platform::MacOS
-        } else {
-            // TODO:
-            // Platform::FreeBsd,
-            // Platform::Ios,
-            // Platform::Android,
-            // Platform::Nintendo,
-            // Platform::Xbox,
-            // Platform::PlayStation,
-            // Platform::Dive,
-            // Platform::Fuchsia,
-            // Platform::Redox,
-            Platform::Unknown(string.to_string())
-        }
+        Platform::Wasm
+    }
+
+    #[inline(always)]
+    fn platform_detected(self) -> bool {
+        true
     }
 
     #[inline(always)]
@@ -232,4 +235,34 @@ impl Target for Os {
             Arch::Wasm32
         })
     }
+
+    #[inline(always)]
+    fn native_arch(self) -> Result<Arch> {
+        self.arch()
+    }
+
+    #[inline(always)]
+    fn isatty(self, _fd: c_int) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn output_kind(self, _fd: c_int) -> OutputKind {
+        OutputKind::Redirected
+    }
+
+    #[inline(always)]
+    fn libc(self) -> Libc {
+        Libc::Unknown
+    }
+
+    fn available_parallelism(self) -> Option<usize> {
+        self.cpu_count()
+    }
+
+    fn cpu_count(self) -> Option<usize> {
+        let cpus = window()?.navigator().hardware_concurrency();
+
+        Some(cpus as usize).filter(|cpus| *cpus > 0)
+    }
 }
@@ -1,5 +1,5 @@
 use std::{
-    convert::TryInto,
+    convert::{TryFrom, TryInto},
     ffi::OsString,
     io::{Error, ErrorKind},
     mem::MaybeUninit,
@@ -12,8 +12,9 @@ use std::{
 
 use crate::{
     conversions,
-    os::{Os, Target},
-    Arch, DesktopEnv, Language, Platform, Result,
+    os::{unsupported, Os, Target},
+    Arch, DesktopEnv, DistroInfo, GroupId, Language, Libc, OutputKind,
+    Platform, Result, UnameInfo, UserId,
 };
 
 #[repr(C)]
@@ -106,6 +107,29 @@ extern "system" {
         pcch_languages_buffer: *mut c_ulong,
     ) -> c_int;
     fn GetNativeSystemInfo(system_info: *mut SystemInfo);
+    fn GetCurrentProcess() -> *mut c_void;
+    fn GetStdHandle(std_handle: c_ulong) -> *mut c_void;
+    fn GetConsoleMode(console_handle: *mut c_void, mode: *mut c_ulong)
+        -> c_int;
+    fn GetFileType(file: *mut c_void) -> c_ulong;
+}
+
+/// Get the handle of a standard stream from its file descriptor.
+fn std_handle(fd: c_int) -> Option<*mut c_void> {
+    // Only the standard streams have file descriptors on Windows
+    let std_handle = match fd {
+        0 => -10i32,
+        1 => -11i32,
+        2 => -12i32,
+        _ => return None,
+    };
+    let handle = unsafe { GetStdHandle(std_handle as c_ulong) };
+
+    if handle.is_null() || handle as isize == -1 {
+        return None;
+    }
+
+    Some(handle)
 }
 
 struct LangIter {
@@ -173,9 +197,184 @@ pub(crate) fn lang() -> impl Iterator<Item = String> {
     LangIter { array, index }
 }
 
+extern "system" {
+    fn LoadLibraryExW(
+        filename: *const u16,
+        hfile: *mut c_void,
+        dwflags: c_ulong,
+    ) -> *mut c_void;
+    fn FreeLibrary(hmodule: *mut c_void) -> i32;
+    fn GetProcAddress(
+        hmodule: *mut c_void,
+        procname: *const c_char,
+    ) -> *mut c_void;
+}
+
+fn os_version() -> Result<OsVersionInfoEx> {
+    // Due to MingW Limitations, we must dynamically load ntdll.dll
+    let mut path = "ntdll.dll\0".encode_utf16().collect::<Vec<u16>>();
+    let path = path.as_mut_ptr();
+
+    let inst = unsafe { LoadLibraryExW(path, ptr::null_mut(), 0x0000_0800) };
+
+    if inst.is_null() {
+        return Err(Error::last_os_error());
+    }
+
+    let mut path = "RtlGetVersion\0".bytes().collect::<Vec<u8>>();
+    let path = path.as_mut_ptr().cast();
+    let func = unsafe { GetProcAddress(inst, path) };
+
+    if func.is_null() {
+        if unsafe { FreeLibrary(inst) } == 0 {
+            return Err(Error::last_os_error());
+        }
+
+        return Err(Error::last_os_error());
+    }
+
+    let get_version: unsafe extern "system" fn(a: *mut OsVersionInfoEx) -> u32 =
+        unsafe { std::mem::transmute(func) };
+
+    let mut version = MaybeUninit::<OsVersionInfoEx>::zeroed();
+
+    unsafe {
+        (*version.as_mut_ptr()).os_version_info_size =
+            std::mem::size_of::<OsVersionInfoEx>() as u32;
+        get_version(version.as_mut_ptr());
+
+        if FreeLibrary(inst) == 0 {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(version.assume_init())
+    }
+}
+
+/// Get the `IMAGE_FILE_MACHINE_*` value of the host, which differs from the
+/// process's when running under emulation (such as x64 on ARM64).
+///
+/// Returns `None` if `IsWow64Process2()` is unavailable (before Windows 10
+/// version 1511), so it must be dynamically loaded.
+fn native_machine() -> Option<c_ushort> {
+    let mut path = "kernel32.dll\0".encode_utf16().collect::<Vec<u16>>();
+    let path = path.as_mut_ptr();
+
+    let inst = unsafe { LoadLibraryExW(path, ptr::null_mut(), 0x0000_0800) };
+
+    if inst.is_null() {
+        return None;
+    }
+
+    let mut path = "IsWow64Process2\0".bytes().collect::<Vec<u8>>();
+    let path = path.as_mut_ptr().cast();
+    let func = unsafe { GetProcAddress(inst, path) };
+    let mut process_machine: c_ushort = 0;
+    let mut native_machine: c_ushort = 0;
+    let found = !func.is_null()
+        && unsafe {
+            let is_wow64_process2: unsafe extern "system" fn(
+                process: *mut c_void,
+                process_machine: *mut c_ushort,
+                native_machine: *mut c_ushort,
+            )
+                -> c_int = std::mem::transmute(func);
+
+            is_wow64_process2(
+                GetCurrentProcess(),
+                &mut process_machine,
+                &mut native_machine,
+            ) != 0
+        };
+
+    unsafe { FreeLibrary(inst) };
+
+    Some(native_machine).filter(|_| found)
+}
+
+/// Map the `wProcessorArchitecture` and `dwProcessorType` fields of
+/// `SYSTEM_INFO` to an [`Arch`].
+fn arch_from_system_info(
+    processor_architecture: c_ushort,
+    processor_type: c_ulong,
+) -> Result<Arch> {
+    fn proc(processor_type: c_ulong) -> Result<Arch, c_ulong> {
+        Ok(match processor_type {
+            // PROCESSOR_INTEL_386
+            386 => Arch::I386,
+            // PROCESSOR_INTEL_486
+            486 => Arch::Unknown("I486".to_string()),
+            // PROCESSOR_INTEL_PENTIUM
+            586 => Arch::I586,
+            // PROCESSOR_INTEL_IA64
+            2200 => Arch::Unknown("IA64".to_string()),
+            // PROCESSOR_AMD_X8664
+            8664 => Arch::X64,
+            v => return Err(v),
+        })
+    }
+
+    // Supported architectures, source:
+    // https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/ns-sysinfoapi-system_info#members
+    Ok(match processor_architecture {
+        // PROCESSOR_ARCHITECTURE_INTEL
+        0 => Arch::I686,
+        // PROCESSOR_ARCHITECTURE_ARM
+        5 => Arch::ArmV6,
+        // PROCESSOR_ARCHITECTURE_IA64
+        6 => Arch::Unknown("IA64".to_string()),
+        // PROCESSOR_ARCHITECTURE_AMD64
+        9 => Arch::X64,
+        // PROCESSOR_ARCHITECTURE_ARM64
+        12 => Arch::Arm64,
+        // PROCESSOR_ARCHITECTURE_UNKNOWN
+        0xFFFF => proc(processor_type).map_err(|e| {
+            Error::new(ErrorKind::InvalidData, format!("Unknown arch: {}", e))
+        })?,
+        invalid => proc(processor_type).map_err(|e| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid arch: {}/{}", invalid, e),
+            )
+        })?,
+    })
+}
+
+/// Map an `IMAGE_FILE_MACHINE_*` value to an [`Arch`].
+fn arch_from_image_file_machine(machine: c_ushort) -> Result<Arch> {
+    // Source:
+    // https://learn.microsoft.com/en-us/windows/win32/sysinfo/image-file-machine-constants
+    Ok(match machine {
+        // IMAGE_FILE_MACHINE_I386
+        0x014C => Arch::I686,
+        // IMAGE_FILE_MACHINE_ARMNT
+        0x01C4 => Arch::ArmV7,
+        // IMAGE_FILE_MACHINE_IA64
+        0x0200 => Arch::Unknown("IA64".to_string()),
+        // IMAGE_FILE_MACHINE_AMD64
+        0x8664 => Arch::X64,
+        // IMAGE_FILE_MACHINE_ARM64
+        0xAA64 => Arch::Arm64,
+        // IMAGE_FILE_MACHINE_ARM64EC
+        0xA641 => Arch::Arm64Ec,
+        unknown => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unknown arch: {:#06x}", unknown),
+            ))
+        }
+    })
+}
+
 impl Target for Os {
     fn langs(self) -> Vec<Language> {
-        todo!()
+        lang()
+            .filter_map(|lang| conversions::language_from_locale(&lang))
+            .collect()
+    }
+
+    fn primary_lang(self) -> Option<Language> {
+        lang().find_map(|lang| conversions::language_from_locale(&lang))
     }
 
     fn realname(self) -> Result<OsString> {
@@ -253,6 +452,26 @@ impl Target for Os {
         Ok(OsString::from_wide(&name))
     }
 
+    #[inline(always)]
+    fn user(self) -> Result<(OsString, OsString)> {
+        Ok((self.username()?, Os.realname()?))
+    }
+
+    #[inline(always)]
+    fn login(self) -> Result<OsString> {
+        self.username()
+    }
+
+    #[inline(always)]
+    fn uid(self) -> Result<UserId> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn gid(self) -> Result<GroupId> {
+        Err(unsupported())
+    }
+
     fn devicename(self) -> Result<OsString> {
         // Step 1. Retreive the entire length of the device name
         let mut size = 0;
@@ -296,6 +515,16 @@ impl Target for Os {
         Ok(OsString::from_wide(&name))
     }
 
+    #[inline(always)]
+    fn pretty_hostname(self) -> Result<OsString> {
+        self.devicename()
+    }
+
+    #[inline(always)]
+    fn local_hostname(self) -> Result<OsString> {
+        Err(unsupported())
+    }
+
     fn hostname(self) -> Result<String> {
         // Step 1. Retreive the entire length of the username
         let mut size = 0;
@@ -340,59 +569,7 @@ impl Target for Os {
     }
 
     fn distro(self) -> Result<String> {
-        // Due to MingW Limitations, we must dynamically load ntdll.dll
-        extern "system" {
-            fn LoadLibraryExW(
-                filename: *const u16,
-                hfile: *mut c_void,
-                dwflags: c_ulong,
-            ) -> *mut c_void;
-            fn FreeLibrary(hmodule: *mut c_void) -> i32;
-            fn GetProcAddress(
-                hmodule: *mut c_void,
-                procname: *const c_char,
-            ) -> *mut c_void;
-        }
-
-        let mut path = "ntdll.dll\0".encode_utf16().collect::<Vec<u16>>();
-        let path = path.as_mut_ptr();
-
-        let inst =
-            unsafe { LoadLibraryExW(path, ptr::null_mut(), 0x0000_0800) };
-
-        if inst.is_null() {
-            return Err(Error::last_os_error());
-        }
-
-        let mut path = "RtlGetVersion\0".bytes().collect::<Vec<u8>>();
-        let path = path.as_mut_ptr().cast();
-        let func = unsafe { GetProcAddress(inst, path) };
-
-        if func.is_null() {
-            if unsafe { FreeLibrary(inst) } == 0 {
-                return Err(Error::last_os_error());
-            }
-
-            return Err(Error::last_os_error());
-        }
-
-        let get_version: unsafe extern "system" fn(
-            a: *mut OsVersionInfoEx,
-        ) -> u32 = unsafe { std::mem::transmute(func) };
-
-        let mut version = MaybeUninit::<OsVersionInfoEx>::zeroed();
-
-        let version = unsafe {
-            (*version.as_mut_ptr()).os_version_info_size =
-                std::mem::size_of::<OsVersionInfoEx>() as u32;
-            get_version(version.as_mut_ptr());
-
-            if FreeLibrary(inst) == 0 {
-                return Err(Error::last_os_error());
-            }
-
-            version.assume_init()
-        };
+        let version = os_version()?;
 
         let product = match version.product_type {
             1 => "Workstation",
@@ -410,66 +587,208 @@ impl Target for Os {
         ))
     }
 
+    fn distro_info(self) -> Result<DistroInfo> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn kernel_version(self) -> Result<String> {
+        let version = os_version()?;
+
+        Ok(format!(
+            "{}.{}.{}",
+            version.major_version, version.minor_version, version.build_number,
+        ))
+    }
+
+    #[inline(always)]
+    fn uname_info(self) -> Result<UnameInfo> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn model_identifier(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn device_model(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn device_vendor(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn cwd_fs_type(self) -> Result<String> {
+        Err(unsupported())
+    }
+
+    #[inline(always)]
+    fn fqdn_local(self, _hostname: &str) -> Result<String> {
+        Err(unsupported())
+    }
+
     #[inline(always)]
     fn desktop_env(self) -> DesktopEnv {
         DesktopEnv::Windows
     }
 
+    #[inline(always)]
+    fn window_manager(self) -> Option<String> {
+        None
+    }
+
+    #[inline(always)]
+    fn desktop_env_version(self) -> Option<String> {
+        None
+    }
+
     #[inline(always)]
     fn platform(self) -> Platform {
         Platform::Windows
     }
 
+    #[inline(always)]
+    fn platform_detected(self) -> bool {
+        true
+    }
+
     #[inline(always)]
     fn arch(self) -> Result<Arch> {
-        fn proc(processor_type: c_ulong) -> Result<Arch, c_ulong> {
-            Ok(match processor_type {
-                // PROCESSOR_INTEL_386
-                386 => Arch::I386,
-                // PROCESSOR_INTEL_486
-                486 => Arch::Unknown("I486".to_string()),
-                // PROCESSOR_INTEL_PENTIUM
-                586 => Arch::I586,
-                // PROCESSOR_INTEL_IA64
-                2200 => Arch::Unknown("IA64".to_string()),
-                // PROCESSOR_AMD_X8664
-                8664 => Arch::X64,
-                v => return Err(v),
-            })
+        let buf: SystemInfo = unsafe {
+            let mut buf = MaybeUninit::uninit();
+            GetNativeSystemInfo(buf.as_mut_ptr());
+            buf.assume_init()
+        };
+
+        arch_from_system_info(buf.processor_architecture, buf.processor_type)
+    }
+
+    fn native_arch(self) -> Result<Arch> {
+        let machine = match native_machine() {
+            Some(machine) => machine,
+            None => return self.arch(),
+        };
+
+        arch_from_image_file_machine(machine)
+    }
+
+    fn isatty(self, fd: c_int) -> bool {
+        let handle = match std_handle(fd) {
+            Some(handle) => handle,
+            None => return false,
+        };
+        let mut mode = 0;
+
+        unsafe { GetConsoleMode(handle, &mut mode) != 0 }
+    }
+
+    fn output_kind(self, fd: c_int) -> OutputKind {
+        if self.isatty(fd) {
+            return OutputKind::Terminal;
         }
 
+        let handle = match std_handle(fd) {
+            Some(handle) => handle,
+            None => return OutputKind::Redirected,
+        };
+
+        match unsafe { GetFileType(handle) } {
+            // FILE_TYPE_DISK
+            0x0001 => OutputKind::File,
+            // FILE_TYPE_PIPE
+            0x0003 => OutputKind::Pipe,
+            // FILE_TYPE_CHAR (such as `NUL`), FILE_TYPE_REMOTE, ...
+            _ => OutputKind::Redirected,
+        }
+    }
+
+    #[inline(always)]
+    fn libc(self) -> Libc {
+        Libc::Unknown
+    }
+
+    fn available_parallelism(self) -> Option<usize> {
+        self.cpu_count()
+    }
+
+    fn cpu_count(self) -> Option<usize> {
         let buf: SystemInfo = unsafe {
             let mut buf = MaybeUninit::uninit();
             GetNativeSystemInfo(buf.as_mut_ptr());
             buf.assume_init()
         };
 
-        // Supported architectures, source:
-        // https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/ns-sysinfoapi-system_info#members
-        Ok(match buf.processor_architecture {
-            // PROCESSOR_ARCHITECTURE_INTEL
-            0 => Arch::I686,
-            // PROCESSOR_ARCHITECTURE_ARM
-            5 => Arch::ArmV6,
-            // PROCESSOR_ARCHITECTURE_IA64
-            6 => Arch::Unknown("IA64".to_string()),
-            // PROCESSOR_ARCHITECTURE_AMD64
-            9 => Arch::X64,
-            // PROCESSOR_ARCHITECTURE_ARM64
-            12 => Arch::Arm64,
-            // PROCESSOR_ARCHITECTURE_UNKNOWN
-            0xFFFF => proc(buf.processor_type).map_err(|e| {
-                Error::new(
-                    ErrorKind::InvalidData,
-                    format!("Unknown arch: {}", e),
-                )
-            })?,
-            invalid => proc(buf.processor_type).map_err(|e| {
-                Error::new(
-                    ErrorKind::InvalidData,
-                    format!("Invalid arch: {}/{}", invalid, e),
-                )
-            })?,
-        })
+        usize::try_from(buf.number_of_processors)
+            .ok()
+            .filter(|cpus| *cpus > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_info_arch() {
+        for (processor_architecture, processor_type, expected) in &[
+            (0, 586, Arch::I686),
+            (5, 0, Arch::ArmV6),
+            (6, 2200, Arch::Unknown("IA64".to_string())),
+            (9, 8664, Arch::X64),
+            (12, 0, Arch::Arm64),
+            (0xFFFF, 386, Arch::I386),
+            (0xFFFF, 486, Arch::Unknown("I486".to_string())),
+            (0xFFFF, 586, Arch::I586),
+            (0xFFFF, 8664, Arch::X64),
+            (42, 8664, Arch::X64),
+        ] {
+            assert_eq!(
+                arch_from_system_info(*processor_architecture, *processor_type)
+                    .ok()
+                    .as_ref(),
+                Some(expected),
+                "{}/{}",
+                processor_architecture,
+                processor_type,
+            );
+        }
+
+        for (processor_architecture, processor_type) in &[(0xFFFF, 0), (42, 0)]
+        {
+            assert_eq!(
+                arch_from_system_info(*processor_architecture, *processor_type)
+                    .unwrap_err()
+                    .kind(),
+                ErrorKind::InvalidData,
+            );
+        }
+    }
+    #[test]
+    fn image_file_machine_arch() {
+        for (machine, expected) in &[
+            (0x014C, Arch::I686),
+            (0x01C4, Arch::ArmV7),
+            (0x0200, Arch::Unknown("IA64".to_string())),
+            (0x8664, Arch::X64),
+            (0xAA64, Arch::Arm64),
+            (0xA641, Arch::Arm64Ec),
+        ] {
+            assert_eq!(
+                arch_from_image_file_machine(*machine).ok().as_ref(),
+                Some(expected),
+                "{:#06x}",
+                machine,
+            );
+        }
+
+        // IMAGE_FILE_MACHINE_UNKNOWN
+        assert_eq!(
+            arch_from_image_file_machine(0).unwrap_err().kind(),
+            ErrorKind::InvalidData,
+        );
     }
 }
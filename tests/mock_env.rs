@@ -0,0 +1,154 @@
+//! Check the `WHOAMI_<FIELD>` overrides of the `mock-env` feature, by running
+//! this test binary again as a child process with them set.
+
+#![cfg(feature = "mock-env")]
+
+use std::{env, process::Command};
+
+const CHILD: &str = "WHOAMI_TEST_MOCK_ENV_CHILD";
+
+/// The overrides that are set (or removed) for the child process.
+const VARS: &[&str] = &[
+    "WHOAMI_USERNAME",
+    "WHOAMI_REALNAME",
+    "WHOAMI_DEVICENAME",
+    "WHOAMI_HOSTNAME",
+    "WHOAMI_DISTRO",
+    "WHOAMI_ARCH",
+];
+
+/// Run as the child process; reports each getter as `name=value` on standard
+/// error (or `name=!` on failure).
+#[test]
+fn mock_env_child() {
+    fn report(name: &str, value: std::io::Result<String>) {
+        eprintln!("{}={}", name, value.unwrap_or_else(|_| "!".to_string()));
+    }
+
+    if env::var_os(CHILD).is_some() {
+        report("username", whoami::fallible::username());
+        report(
+            "username_normalized",
+            whoami::fallible::username_normalized(),
+        );
+        report("realname", whoami::fallible::realname());
+        report("devicename", whoami::fallible::devicename());
+        report("hostname", whoami::fallible::hostname());
+        report("distro", whoami::fallible::distro());
+        report("arch", Ok(whoami::arch().to_string()));
+    }
+}
+
+/// Run the child process with `vars` set, and all other overrides removed.
+fn child_values(vars: &[(&str, &str)]) -> Vec<String> {
+    let mut command = Command::new(env::current_exe().unwrap());
+
+    for var in VARS {
+        command.env_remove(var);
+    }
+
+    let output = command
+        .args(&["mock_env_child", "--exact", "--nocapture"])
+        .env(CHILD, "1")
+        .envs(vars.iter().cloned())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "{}", stderr);
+
+    stderr
+        .lines()
+        .filter(|line| line.contains('='))
+        .map(String::from)
+        .collect()
+}
+
+/// Get the child's reported value for `name`.
+fn value<'a>(values: &'a [String], name: &str) -> &'a str {
+    values
+        .iter()
+        .find(|line| {
+            line.starts_with(name) && line[name.len()..].starts_with('=')
+        })
+        .map(|line| &line[name.len() + 1..])
+        .unwrap_or_else(|| panic!("No {} in: {:?}", name, values))
+}
+
+#[test]
+fn overrides() {
+    let values = child_values(&[
+        ("WHOAMI_USERNAME", "JDoe"),
+        ("WHOAMI_REALNAME", "Jane Doe"),
+        ("WHOAMI_DEVICENAME", "Jane's Laptop"),
+        ("WHOAMI_HOSTNAME", "mock-host"),
+        ("WHOAMI_DISTRO", "Mock OS 1.0"),
+    ]);
+
+    assert_eq!(value(&values, "username"), "JDoe");
+    assert_eq!(value(&values, "username_normalized"), "jdoe");
+    assert_eq!(value(&values, "realname"), "Jane Doe");
+    assert_eq!(value(&values, "devicename"), "Jane's Laptop");
+    assert_eq!(value(&values, "hostname"), "mock-host");
+    assert_eq!(value(&values, "distro"), "Mock OS 1.0");
+}
+
+#[test]
+fn arch_override() {
+    for arch in &[
+        whoami::Arch::Riscv64,
+        whoami::Arch::PowerPc64,
+        whoami::Arch::Sparc,
+        whoami::Arch::Unknown("vax".to_string()),
+    ] {
+        let values = child_values(&[("WHOAMI_ARCH", &arch.to_string())]);
+
+        assert_eq!(value(&values, "arch"), arch.to_string());
+    }
+}
+
+#[test]
+fn username_mixed_case() {
+    for (username, expected) in &[
+        ("JDoe", "jdoe"),
+        ("jdoe", "jdoe"),
+        ("Émile.DUPONT", "émile.dupont"),
+        ("CORP\\JDoe", "corp\\jdoe"),
+    ] {
+        let values = child_values(&[("WHOAMI_USERNAME", username)]);
+
+        assert_eq!(value(&values, "username"), *username);
+        assert_eq!(value(&values, "username_normalized"), *expected);
+    }
+}
+
+#[test]
+fn no_overrides() {
+    let or_failed =
+        |value: std::io::Result<String>| value.unwrap_or_else(|_| "!".into());
+
+    // The overrides would apply to this process too
+    if VARS.iter().any(|var| env::var_os(var).is_some()) {
+        return;
+    }
+
+    let values = child_values(&[]);
+
+    assert_eq!(
+        value(&values, "username"),
+        or_failed(whoami::fallible::username()),
+    );
+    assert_eq!(
+        value(&values, "username_normalized"),
+        or_failed(whoami::fallible::username()).to_lowercase(),
+    );
+    assert_eq!(
+        value(&values, "hostname"),
+        or_failed(whoami::fallible::hostname()),
+    );
+    assert_eq!(
+        value(&values, "distro"),
+        or_failed(whoami::fallible::distro()),
+    );
+    assert_eq!(value(&values, "arch"), whoami::arch().to_string());
+}
@@ -0,0 +1,55 @@
+//! Check `output_kind()` with standard output connected to a pipe and to a
+//! file, by running this test binary again as a child process.
+
+use std::{
+    env, fs,
+    process::{Command, Stdio},
+};
+
+const CHILD: &str = "WHOAMI_TEST_OUTPUT_KIND_CHILD";
+
+/// Run as the child process; reports `output_kind()` (as `OutputKind`'s
+/// `Debug` name) on standard error, since standard output is what's being
+/// checked.
+#[test]
+fn output_kind_child() {
+    if env::var_os(CHILD).is_some() {
+        eprintln!("output_kind={:?}", whoami::output_kind());
+    }
+}
+
+/// Run the child process with standard output connected to `stdout`.
+fn child_output_kind(stdout: Stdio) -> String {
+    let output = Command::new(env::current_exe().unwrap())
+        .args(&["output_kind_child", "--exact", "--nocapture"])
+        .env(CHILD, "1")
+        .stdout(stdout)
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "{}", stderr);
+
+    stderr
+        .lines()
+        .find(|line| line.starts_with("output_kind="))
+        .map(|line| line["output_kind=".len()..].to_string())
+        .unwrap_or_else(|| panic!("No output kind in: {}", stderr))
+}
+
+#[test]
+fn output_kind_pipe() {
+    assert_eq!(child_output_kind(Stdio::piped()), "Pipe");
+}
+
+#[test]
+fn output_kind_file() {
+    let path = env::temp_dir()
+        .join(format!("whoami-output-kind-{}.txt", std::process::id()));
+    let file = fs::File::create(&path).unwrap();
+    let kind = child_output_kind(Stdio::from(file));
+
+    fs::remove_file(&path).unwrap();
+    assert_eq!(kind, "File");
+}